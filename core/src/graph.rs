@@ -1,8 +1,10 @@
 use std::{collections::BTreeSet, hash::Hash};
 
+use hashbrown::HashMap;
 use itertools::Itertools;
 use petgraph::algo::kosaraju_scc;
 use petgraph::matrix_graph::MatrixGraph;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences, IntoNodeIdentifiers};
 
 pub fn build_graph<T: Clone + Eq + Hash, Iter: Iterator<Item = T>>(
     token_iter: Iter,
@@ -36,3 +38,150 @@ pub fn anchor_nodes<T: Clone + Eq + Hash + Ord>(g: MatrixGraph<T, ()>) -> BTreeS
         .unwrap_or_default();
     nodes
 }
+
+/// Builds a co-occurrence-weighted token graph: one node per distinct token
+/// in `token_iter`, with an undirected edge between every pair whose
+/// `weight` (e.g. corpus-wide co-occurrence count) is greater than zero.
+/// Edge-free (zero-weight) pairs are omitted rather than added as
+/// zero-weight edges, so [`mst_anchor_nodes`]'s `min_weight` pruning doesn't
+/// have to distinguish "never co-occurred" from "co-occurred, but rarely".
+pub fn build_cooccurrence_graph<T: Clone + Eq + Hash, Iter: Iterator<Item = T>>(
+    token_iter: Iter,
+    weight: impl Fn(&T, &T) -> f32,
+) -> MatrixGraph<T, f32> {
+    let tokens = token_iter.collect::<Vec<_>>();
+    let mut graph = MatrixGraph::with_capacity(tokens.len());
+    let nodes = tokens
+        .iter()
+        .unique()
+        .cloned()
+        .map(|tok| graph.add_node(tok))
+        .collect::<Vec<_>>();
+    nodes.iter().tuple_combinations().for_each(|(n1, n2)| {
+        let w = weight(graph.node_weight(*n1), graph.node_weight(*n2));
+        if w > 0.0 {
+            graph.add_edge(*n1, *n2, w);
+            graph.add_edge(*n2, *n1, w);
+        }
+    });
+    graph
+}
+
+/// Disjoint-set forest over a weighted graph's node indices, used by
+/// [`mst_anchor_nodes`] to grow maximum-spanning-tree components as edges
+/// are folded in descending-weight order.
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            self.parent[x] = self.find(self.parent[x]);
+        }
+        self.parent[x]
+    }
+
+    /// Merges the sets containing `a` and `b`; returns whether they were
+    /// previously distinct (i.e. whether this union actually joined them).
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+        self.parent[root_a] = root_b;
+        true
+    }
+}
+
+/// Selects an anchor token set from a co-occurrence-weighted graph via a
+/// maximum-spanning-forest pass: edges at or above `min_weight` are visited
+/// in descending-weight order and folded into a union-find, so two tokens
+/// end up in the same component only through a chain of strong
+/// co-occurrence, not merely because *some* rare, spurious co-occurrence
+/// happens to bridge them. The largest resulting component is returned as
+/// the anchor set, an alternative to [`anchor_nodes`]'s all-or-nothing
+/// largest-SCC heuristic that weighs how strongly, not just whether, tokens
+/// are associated.
+pub fn mst_anchor_nodes<T: Clone + Eq + Hash + Ord>(
+    g: &MatrixGraph<T, f32>,
+    min_weight: f32,
+) -> BTreeSet<T> {
+    let nodes = g.node_identifiers().collect::<Vec<_>>();
+    let index_of = nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, node)| (*node, idx))
+        .collect::<HashMap<_, _>>();
+
+    let mut edges = g
+        .edge_references()
+        .filter(|edge| *edge.weight() >= min_weight)
+        .map(|edge| (index_of[&edge.source()], index_of[&edge.target()], *edge.weight()))
+        .collect::<Vec<_>>();
+    edges.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let mut uf = UnionFind::new(nodes.len());
+    for (src, dst, _) in edges {
+        uf.union(src, dst);
+    }
+
+    let mut components: HashMap<usize, Vec<_>> = HashMap::new();
+    for (idx, node) in nodes.iter().enumerate() {
+        let root = uf.find(idx);
+        components.entry(root).or_default().push(*node);
+    }
+    components
+        .into_values()
+        .max_by_key(|members| members.len())
+        .map(|members| members.iter().map(|node| g.node_weight(*node)).cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mst_anchor_nodes_prunes_weak_bridge() {
+        // `a`-`b`-`c` and `d`-`e` are each strongly co-occurring, linked only
+        // by a single weak `c`-`d` edge that shouldn't be enough to merge
+        // the two groups into one anchor set.
+        let weights = HashMap::from([
+            (("a", "b"), 5.0),
+            (("b", "c"), 5.0),
+            (("d", "e"), 5.0),
+            (("c", "d"), 1.0),
+        ]);
+        let weight = |t1: &&str, t2: &&str| {
+            weights
+                .get(&(*t1, *t2))
+                .or_else(|| weights.get(&(*t2, *t1)))
+                .copied()
+                .unwrap_or(0.0)
+        };
+        let graph = build_cooccurrence_graph(["a", "b", "c", "d", "e"].into_iter(), weight);
+
+        assert_eq!(
+            mst_anchor_nodes(&graph, 2.0),
+            BTreeSet::from(["a", "b", "c"])
+        );
+    }
+
+    #[test]
+    fn test_mst_anchor_nodes_keeps_uniformly_connected_graph() {
+        let weight = |_: &&str, _: &&str| 1.0;
+        let graph = build_cooccurrence_graph(["x", "y", "z"].into_iter(), weight);
+
+        assert_eq!(
+            mst_anchor_nodes(&graph, 0.0),
+            BTreeSet::from(["x", "y", "z"])
+        );
+    }
+}
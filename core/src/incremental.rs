@@ -0,0 +1,290 @@
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::Itertools;
+
+use crate::{
+    graph::{anchor_nodes, build_graph},
+    template::{shared_slices, templates},
+    tokenizer::Token,
+    traits::Tokenize,
+    Tokenizer,
+};
+
+pub type ClusterId = usize;
+
+/// Running single/pairwise token occurrence counts, gathered online as
+/// messages are pushed through [`IncrementalParser`]. Mirrors
+/// [`crate::token_record::OwnedTokenRecord`]'s `occurence`/`dependency`
+/// shape, but grows incrementally instead of being computed once over a
+/// fixed corpus, since `IncrementalParser` never sees its full input up
+/// front.
+#[derive(Default)]
+struct TokenStats {
+    soc: HashMap<String, u32>,
+    poc: HashMap<BTreeSet<String>, u32>,
+}
+
+impl TokenStats {
+    fn dependency(&self, eve: &str, con: &str) -> Option<f32> {
+        let double = *self
+            .poc
+            .get(&BTreeSet::from([eve.to_owned(), con.to_owned()]))?;
+        let single = *self.soc.get(eve)?;
+        Some(double as f32 / single as f32)
+    }
+
+    /// Folds one message's distinct tokens into the running counts.
+    fn observe<'a>(&mut self, tokens: impl Iterator<Item = Token<'a>>) {
+        let toks = tokens.map(|tok| tok.as_str()).unique().collect::<Vec<_>>();
+        for tok in &toks {
+            self.soc
+                .entry((*tok).to_owned())
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        for (tok1, tok2) in toks.iter().tuple_combinations() {
+            self.poc
+                .entry(BTreeSet::from([(*tok1).to_owned(), (*tok2).to_owned()]))
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+    }
+}
+
+/// Minimal disjoint-set forest over cluster indices, used by
+/// [`IncrementalParser`] to merge clusters a later message turns out to
+/// bridge.
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new() -> Self {
+        Self { parent: Vec::new() }
+    }
+
+    fn make_set(&mut self) -> usize {
+        let id = self.parent.len();
+        self.parent.push(id);
+        id
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges `other`'s set into `keep`'s, so `find(keep)` stays the root.
+    fn union(&mut self, keep: usize, other: usize) {
+        let (root_keep, root_other) = (self.find(keep), self.find(other));
+        if root_keep != root_other {
+            self.parent[root_other] = root_keep;
+        }
+    }
+}
+
+struct ClusterState<'a> {
+    anchors: BTreeSet<Token<'a>>,
+    messages: Vec<&'a str>,
+}
+
+fn jaccard_similarity<'a>(a: &BTreeSet<Token<'a>>, b: &BTreeSet<Token<'a>>) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
+
+/// Streaming counterpart to [`crate::template::templates`]/[`crate::shared_slices`]: ingests
+/// messages one at a time and maintains a growing set of template clusters
+/// instead of requiring the full corpus up front.
+///
+/// Each message's anchor/key-node token set is computed the same way
+/// [`crate::Matcher::assign`] does per-message: tokens are filtered, a
+/// dependency-threshold graph is built over the survivors (via
+/// [`build_graph`], edge `(t1, t2)` present when `t1` predicts `t2` past
+/// `dependency_threshold`), and the largest strongly connected component is
+/// kept as the anchor set (via [`anchor_nodes`]), with the
+/// `SpecialWhite`/`SpecialBlack` force-in/force-out adjustment on top. The
+/// one difference from `Matcher` is where the dependency statistics come
+/// from: `Matcher` replays a [`crate::token_record::TokenRecord`] frozen over
+/// a full training corpus, while `IncrementalParser` has no corpus up front,
+/// so it grows its own [`TokenStats`] online, folding each message in before
+/// computing that same message's anchors. When a message's anchors overlap
+/// an existing cluster's past `jaccard_threshold` the message joins that
+/// cluster (merging it with every other cluster it also overlaps, via
+/// union-find); otherwise it starts a new cluster. This gives O(α) amortized
+/// assignment per message, so unbounded streams can be parsed without
+/// re-scanning history.
+pub struct IncrementalParser<'a> {
+    tokenizer: Tokenizer,
+    jaccard_threshold: f32,
+    dependency_threshold: f32,
+    stats: TokenStats,
+    clusters: Vec<ClusterState<'a>>,
+    uf: UnionFind,
+}
+
+impl<'a> IncrementalParser<'a> {
+    pub fn new(tokenizer: Tokenizer, jaccard_threshold: f32, dependency_threshold: f32) -> Self {
+        Self {
+            tokenizer,
+            jaccard_threshold,
+            dependency_threshold,
+            stats: TokenStats::default(),
+            clusters: Vec::new(),
+            uf: UnionFind::new(),
+        }
+    }
+
+    /// Ingests `msg`, assigning it to a (possibly newly created, possibly
+    /// merged) cluster, and returns that cluster's stable id.
+    pub fn push(&mut self, msg: &'a str) -> ClusterId {
+        let anchors = self.anchor_tokens(msg);
+
+        let matches = (0..self.clusters.len())
+            .filter(|&idx| self.uf.find(idx) == idx)
+            .filter(|&idx| jaccard_similarity(&anchors, &self.clusters[idx].anchors) >= self.jaccard_threshold)
+            .collect::<Vec<_>>();
+
+        let root = match matches.first() {
+            Some(&idx) => idx,
+            None => {
+                let id = self.uf.make_set();
+                self.clusters.push(ClusterState {
+                    anchors,
+                    messages: vec![msg],
+                });
+                return id;
+            }
+        };
+
+        for &other in &matches[1..] {
+            self.uf.union(root, other);
+            let absorbed_messages = std::mem::take(&mut self.clusters[other].messages);
+            let absorbed_anchors = std::mem::take(&mut self.clusters[other].anchors);
+            self.clusters[root].messages.extend(absorbed_messages);
+            self.clusters[root].anchors = self.clusters[root]
+                .anchors
+                .intersection(&absorbed_anchors)
+                .cloned()
+                .collect();
+        }
+
+        self.clusters[root].anchors = self.clusters[root]
+            .anchors
+            .intersection(&anchors)
+            .cloned()
+            .collect();
+        self.clusters[root].messages.push(msg);
+        root
+    }
+
+    /// Recomputes the mined template of every live cluster by intersecting
+    /// the common slices of its member messages. Clusters absorbed by a
+    /// later merge hold no messages and are skipped.
+    pub fn templates(&self) -> HashMap<ClusterId, String> {
+        self.clusters
+            .iter()
+            .enumerate()
+            .filter(|(_, cluster)| !cluster.messages.is_empty())
+            .map(|(id, cluster)| {
+                let common = shared_slices(
+                    cluster.messages.iter().copied(),
+                    &self.tokenizer,
+                    true,
+                    false,
+                    false,
+                );
+                let template = templates(cluster.messages.iter().copied(), &self.tokenizer, &common)
+                    .into_iter()
+                    .min()
+                    .unwrap_or_default();
+                (id, template)
+            })
+            .collect()
+    }
+
+    fn anchor_tokens(&mut self, msg: &'a str) -> BTreeSet<Token<'a>> {
+        let tokens = self.tokenizer.tokenize(msg);
+        let candidates = tokens
+            .iter()
+            .copied()
+            .filter(|tok| !matches!(tok, Token::Whitespace(_) | Token::Symbolic(_)))
+            .collect::<Vec<_>>();
+        self.stats.observe(candidates.iter().copied());
+        let graph = build_graph(candidates.into_iter(), |tok1, tok2| {
+            self.stats
+                .dependency(tok1.as_str(), tok2.as_str())
+                .unwrap_or(0.0)
+                > self.dependency_threshold
+        });
+        let mut anchors = anchor_nodes(graph);
+        for tok in tokens {
+            match tok {
+                Token::SpecialWhite(_) => {
+                    anchors.insert(tok);
+                }
+                Token::SpecialBlack(_) => {
+                    anchors.remove(&tok);
+                }
+                _ => (),
+            }
+        }
+        anchors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incremental_parser() {
+        let mut parser = IncrementalParser::new(
+            Tokenizer::new(Vec::new(), Vec::new(), Default::default()),
+            0.5,
+            0.5,
+        );
+        let c1 = parser.push("a x1 b");
+        let c2 = parser.push("a x2 b");
+        let c3 = parser.push("c x1 d");
+        assert_eq!(c1, c2);
+        assert_ne!(c1, c3);
+    }
+
+    /// Proves anchor selection is actually filtering by dependency and not
+    /// just keeping every surviving token: after `a`/`b`/`c` have
+    /// co-occurred enough to be mutually dependent, a message that also
+    /// contains two brand-new, one-off tokens should still be recognized as
+    /// the same template. Those one-off tokens are far more dependent on
+    /// `a`/`b`/`c` (perfectly, on their single occurrence) than `a`/`b`/`c`
+    /// are on them (1/4), so they never close a cycle back to `a`/`b`/`c`.
+    /// They do depend on each other and form their own component
+    /// `{n1, n2}`, but it is smaller than `{a, b, c}`, so the largest
+    /// strongly connected component remains `{a, b, c}`. Under the old
+    /// `|_, _| true` predicate every surviving token is always an anchor,
+    /// so this message's anchor set would be `{a, b, c, n1, n2}`, whose
+    /// Jaccard overlap with the existing cluster (3/5) is below the `0.8`
+    /// `jaccard_threshold`, and it would wrongly start a new cluster.
+    #[test]
+    fn test_anchor_tokens_exclude_weakly_dependent_noise() {
+        let mut parser = IncrementalParser::new(
+            Tokenizer::new(Vec::new(), Vec::new(), Default::default()),
+            0.8,
+            0.5,
+        );
+        let c1 = parser.push("a b c");
+        parser.push("a b c");
+        parser.push("a b c");
+        let c4 = parser.push("a b c n1 n2");
+        assert_eq!(c1, c4);
+    }
+}
@@ -1,15 +1,22 @@
 mod graph;
 mod token_record;
+mod incremental;
+mod matcher;
 mod misc;
 mod parser;
 mod template;
 mod token_filter;
 mod tokenizer;
 mod traits;
-pub use misc::compile_into_regex;
+pub use incremental::{ClusterId, IncrementalParser};
+pub use matcher::Matcher;
+pub use misc::{compile_into_regex, compile_placeholder_rules, softmax2};
 pub use parser::Parser;
-pub use tokenizer::Tokenizer;
-pub use template::{shared_slices, parameter_masks};
+pub use tokenizer::{Tokenizer, TEMPLATE_SYMBOLS};
+pub use template::{
+    default_placeholder_rules, extract_parameters, param_types, parameter_masks,
+    parameter_masks_beam, shared_slices, templates_typed, ParamType,
+};
 pub use traits::Tokenize;
 
 #[cfg(test)]
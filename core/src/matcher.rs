@@ -0,0 +1,307 @@
+use std::collections::{BTreeSet, HashMap};
+
+use fancy_regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    graph::{anchor_nodes, build_cooccurrence_graph, build_graph, mst_anchor_nodes},
+    token_filter::StaticFilter,
+    token_record::OwnedTokenRecord,
+    tokenizer::{Token, Tokenizer},
+    traits::{TokenFilter, Tokenize},
+};
+
+/// Owned counterpart of [`Token`], so an anchor-token set can be stored past
+/// the lifetime of the message it was tokenized from (and, via `serde`,
+/// saved to and reloaded from disk).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+enum OwnedToken {
+    Alphabetic(String),
+    Numeric(String),
+    Symbolic(String),
+    Whitespace(String),
+    Impure(String),
+    SpecialWhite(String),
+    SpecialBlack(String),
+}
+
+impl From<Token<'_>> for OwnedToken {
+    fn from(tok: Token<'_>) -> Self {
+        match tok {
+            Token::Alphabetic(s) => OwnedToken::Alphabetic(s.to_owned()),
+            Token::Numeric(s) => OwnedToken::Numeric(s.to_owned()),
+            Token::Symbolic(s) => OwnedToken::Symbolic(s.to_owned()),
+            Token::Whitespace(s) => OwnedToken::Whitespace(s.to_owned()),
+            Token::Impure(s) => OwnedToken::Impure(s.to_owned()),
+            Token::SpecialWhite(s) => OwnedToken::SpecialWhite(s.to_owned()),
+            Token::SpecialBlack(s) => OwnedToken::SpecialBlack(s.to_owned()),
+        }
+    }
+}
+
+/// A [`crate::Parser`] run frozen into an online classifier: the dependency
+/// statistics, tokenizer/filter config, and anchor-token-set-to-cluster map
+/// it learned are all retained, so later messages can be assigned a cluster
+/// id (via [`Matcher::assign`]/[`Matcher::assign_or_insert`]) without
+/// rebuilding either from scratch. Trained state can be saved and reloaded
+/// through `serde`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Matcher {
+    token_record: OwnedTokenRecord,
+    special_white_patterns: Vec<String>,
+    special_black_patterns: Vec<String>,
+    symbols: Vec<char>,
+    threshold: f32,
+    filter_alphabetic: bool,
+    filter_numeric: bool,
+    filter_impure: bool,
+    clusters: HashMap<BTreeSet<OwnedToken>, usize>,
+    next_cluster_id: usize,
+}
+
+impl Matcher {
+    /// Builds a `Matcher` from a trained `TokenRecord`, tokenizer/filter
+    /// config, and the anchor-token sets [`crate::Parser::into_matcher`]
+    /// assigned stable cluster ids to.
+    pub(crate) fn new<'a>(
+        token_record: OwnedTokenRecord,
+        special_white_patterns: Vec<String>,
+        special_black_patterns: Vec<String>,
+        symbols: Vec<char>,
+        threshold: f32,
+        filter_alphabetic: bool,
+        filter_numeric: bool,
+        filter_impure: bool,
+        clusters: impl IntoIterator<Item = (BTreeSet<Token<'a>>, usize)>,
+    ) -> Self {
+        let clusters: HashMap<BTreeSet<OwnedToken>, usize> = clusters
+            .into_iter()
+            .map(|(toks, id)| (toks.into_iter().map(OwnedToken::from).collect(), id))
+            .collect();
+        let next_cluster_id = clusters.values().copied().max().map_or(0, |id| id + 1);
+        Self {
+            token_record,
+            special_white_patterns,
+            special_black_patterns,
+            symbols,
+            threshold,
+            filter_alphabetic,
+            filter_numeric,
+            filter_impure,
+            clusters,
+            next_cluster_id,
+        }
+    }
+
+    /// Tokenizes `msg`, rebuilds its per-message dependency graph with the
+    /// frozen `TokenRecord`/`threshold`, and derives its anchor-token set the
+    /// same way `Parser::parse` does, returning the matching cluster id if
+    /// this exact signature was seen during training, or `None` otherwise.
+    pub fn assign(&self, msg: impl AsRef<str>) -> Option<usize> {
+        self.clusters.get(&self.anchor_tokens(msg.as_ref())).copied()
+    }
+
+    /// Like [`Matcher::assign`], but allocates and remembers a new cluster id
+    /// for a never-seen signature instead of returning `None`, so the model
+    /// grows online as new templates are encountered.
+    pub fn assign_or_insert(&mut self, msg: impl AsRef<str>) -> usize {
+        let anchors = self.anchor_tokens(msg.as_ref());
+        if let Some(&id) = self.clusters.get(&anchors) {
+            return id;
+        }
+        let id = self.next_cluster_id;
+        self.next_cluster_id += 1;
+        self.clusters.insert(anchors, id);
+        id
+    }
+
+    /// Like [`Matcher::assign`], but derives the anchor-token set from a
+    /// co-occurrence-weighted maximum-spanning-forest pass
+    /// ([`build_cooccurrence_graph`]/[`mst_anchor_nodes`]) instead of the
+    /// boolean dependency-threshold graph [`Matcher::assign`] uses. Weighing
+    /// *how strongly* tokens co-occur, rather than only whether they clear a
+    /// single threshold, makes this variant more resistant to a handful of
+    /// rare, spurious co-occurrences pulling unrelated tokens into the same
+    /// anchor set. `min_cooccurrence` is the minimum corpus-wide
+    /// co-occurrence count an edge needs to survive pruning before the
+    /// spanning-forest pass.
+    pub fn assign_weighted(&self, msg: impl AsRef<str>, min_cooccurrence: u32) -> Option<usize> {
+        self.clusters
+            .get(&self.anchor_tokens_weighted(msg.as_ref(), min_cooccurrence))
+            .copied()
+    }
+
+    /// Like [`Matcher::assign_or_insert`], using [`Matcher::assign_weighted`]'s
+    /// anchor-selection strategy instead of [`Matcher::assign`]'s.
+    pub fn assign_or_insert_weighted(&mut self, msg: impl AsRef<str>, min_cooccurrence: u32) -> usize {
+        let anchors = self.anchor_tokens_weighted(msg.as_ref(), min_cooccurrence);
+        if let Some(&id) = self.clusters.get(&anchors) {
+            return id;
+        }
+        let id = self.next_cluster_id;
+        self.next_cluster_id += 1;
+        self.clusters.insert(anchors, id);
+        id
+    }
+
+    fn anchor_tokens_weighted(&self, msg: &str, min_cooccurrence: u32) -> BTreeSet<OwnedToken> {
+        let tokenizer = self.tokenizer();
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let tokens = tokenizer.tokenize(msg);
+        let graph = build_cooccurrence_graph(
+            tokens
+                .iter()
+                .copied()
+                .filter(|tok| filter.token_filter(tok))
+                .filter(|tok| self.token_record.occurence(tok.as_str()).is_some()),
+            |tok1, tok2| {
+                self.token_record
+                    .coocurence(tok1.as_str(), tok2.as_str())
+                    .unwrap_or(0) as f32
+            },
+        );
+        let mut anchor_toks = mst_anchor_nodes(&graph, min_cooccurrence as f32);
+        for tok in tokens {
+            match tok {
+                Token::SpecialWhite(_) => {
+                    anchor_toks.insert(tok);
+                }
+                Token::SpecialBlack(_) => {
+                    anchor_toks.remove(&tok);
+                }
+                _ => (),
+            }
+        }
+        anchor_toks.into_iter().map(OwnedToken::from).collect()
+    }
+
+    fn anchor_tokens(&self, msg: &str) -> BTreeSet<OwnedToken> {
+        let tokenizer = self.tokenizer();
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let tokens = tokenizer.tokenize(msg);
+        let graph = build_graph(
+            tokens
+                .iter()
+                .copied()
+                .filter(|tok| filter.token_filter(tok))
+                .filter(|tok| self.token_record.occurence(tok.as_str()).is_some()),
+            |tok1, tok2| {
+                self.token_record
+                    .dependency(tok1.as_str(), tok2.as_str())
+                    .unwrap_or(0.0)
+                    > self.threshold
+            },
+        );
+        let mut anchor_toks = anchor_nodes(graph);
+        for tok in tokens {
+            match tok {
+                Token::SpecialWhite(_) => {
+                    anchor_toks.insert(tok);
+                }
+                Token::SpecialBlack(_) => {
+                    anchor_toks.remove(&tok);
+                }
+                _ => (),
+            }
+        }
+        anchor_toks.into_iter().map(OwnedToken::from).collect()
+    }
+
+    fn tokenizer(&self) -> Tokenizer {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|p| Regex::new(p).unwrap())
+                .collect::<Vec<_>>()
+        };
+        Tokenizer::new(
+            compile(&self.special_white_patterns),
+            compile(&self.special_black_patterns),
+            self.symbols.iter().copied().collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{token_record::TokenRecord, traits::TokenFilter};
+
+    struct NoFilter;
+    impl TokenFilter for NoFilter {
+        fn token_filter(&self, _tok: &Token) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_assign_weighted_prunes_rare_cooccurrence() {
+        // `n1` appeared once, alongside `a`/`b`/`c`, which co-occurred in
+        // every training message. A `min_cooccurrence` of 2 should keep the
+        // `a`/`b`/`c` edges but prune `n1`'s, leaving `n1` out of the
+        // anchor set instead of folding it in as an equal member.
+        let msgs = ["a b c", "a b c", "a b c", "a b c n1"];
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), Default::default());
+        let token_record = TokenRecord::new(&msgs, &tokenizer, &NoFilter).to_owned();
+
+        let matcher = Matcher::new(
+            token_record,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            0.5,
+            true,
+            false,
+            true,
+            std::iter::empty(),
+        );
+
+        assert_eq!(
+            matcher.anchor_tokens_weighted("a b c n1", 2),
+            BTreeSet::from([
+                OwnedToken::Alphabetic("a".to_owned()),
+                OwnedToken::Alphabetic("b".to_owned()),
+                OwnedToken::Alphabetic("c".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_round_trip_into_matcher_assign() {
+        let msgs = ["a x1 b", "a x2 b", "a x3 b", "c x1 d"];
+        let mut matcher = crate::Parser::default().into_matcher(&msgs);
+
+        let id = matcher.assign_or_insert("z1 z2 z3");
+        assert_eq!(matcher.assign("z1 z2 z3"), Some(id));
+    }
+
+    #[test]
+    fn test_assign_weighted_round_trip() {
+        let msgs = ["a b c", "a b c"];
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), Default::default());
+        let token_record = TokenRecord::new(&msgs, &tokenizer, &NoFilter).to_owned();
+
+        let mut matcher = Matcher::new(
+            token_record,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            0.5,
+            true,
+            false,
+            true,
+            std::iter::empty(),
+        );
+
+        let id = matcher.assign_or_insert_weighted("a b c", 1);
+        assert_eq!(matcher.assign_weighted("a b c", 1), Some(id));
+    }
+}
@@ -1,6 +1,19 @@
 use itertools::Itertools;
 use fancy_regex::Regex;
 
+/// Converts two competing scores into a normalized probability pair via the
+/// two-way softmax `(e^a, e^b) / (e^a + e^b)`, shifted by `max(a, b)` for
+/// numerical stability. Shared by every beam-search scorer in this crate
+/// (and, via [`crate::softmax2`], by consumers outside it) that turns a
+/// "keep vs. drop" score into a pair of probabilities.
+pub fn softmax2(a: f32, b: f32) -> (f32, f32) {
+    let max = a.max(b);
+    let ea = (a - max).exp();
+    let eb = (b - max).exp();
+    let sum = ea + eb;
+    (ea / sum, eb / sum)
+}
+
 
 pub fn compile_into_regex<Item, Iter>(regex_str: Iter) -> Regex
 where
@@ -17,6 +30,26 @@ where
     .unwrap()
 }
 
+/// Compiles a set of `(label, pattern)` rules for use as placeholder
+/// classifiers (e.g. typed template placeholders). Unlike [`compile_into_regex`],
+/// which joins every pattern into a single alternation, each pattern here is
+/// compiled independently so the matching label can be recovered.
+pub fn compile_placeholder_rules<Label, Pattern, Iter>(rules: Iter) -> Vec<(Regex, Label)>
+where
+    Label: AsRef<str>,
+    Pattern: AsRef<str>,
+    Iter: IntoIterator<Item = (Label, Pattern)>,
+{
+    rules
+        .into_iter()
+        .map(|(label, pattern)| {
+            let regex = Regex::new(pattern.as_ref())
+                .unwrap_or_else(|_| panic!("Unable to compile {}", pattern.as_ref()));
+            (regex, label)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,4 +64,13 @@ mod tests {
         assert!(!r.is_match("@").unwrap());
         assert!(!r.is_match("#").unwrap());
     }
+
+    #[test]
+    fn test_compile_placeholder_rules() {
+        let rules = compile_placeholder_rules([("NUM", r"^\d+$"), ("STR", r"^[a-zA-Z]+$")]);
+        let (num, label) = &rules[0];
+        assert_eq!(*label, "NUM");
+        assert!(num.is_match("123").unwrap());
+        assert!(!num.is_match("abc").unwrap());
+    }
 }
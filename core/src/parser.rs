@@ -1,22 +1,26 @@
 use std::{collections::BTreeSet, marker::PhantomData};
 
 use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
 use rayon::prelude::*;
 
 use fancy_regex::Regex;
 
 use crate::{
     graph::{anchor_nodes, build_graph},
-    template::{parameter_masks, shared_slices, templates},
+    matcher::Matcher,
+    template::{param_types, parameter_masks, shared_slices, templates, ParamType},
     token_filter::StaticFilter,
     token_record::TokenRecord,
-    tokenizer::{Token, Tokenizer},
+    tokenizer::{Token, Tokenizer, TEMPLATE_SYMBOLS},
     traits::Tokenize,
 };
 
 type Clusters = Vec<Option<usize>>;
 type Templates = Vec<std::collections::HashSet<String>>;
 type Masks = std::collections::HashMap<String, String>;
+type ParamTypes = Vec<Vec<(usize, ParamType)>>;
+type Labels = Vec<std::collections::HashSet<String>>;
 
 pub struct NoCompute;
 pub struct Compute;
@@ -48,7 +52,7 @@ pub struct Compute;
 ///    .parse(&msgs);
 /// ```
 #[derive(Debug, Clone)]
-pub struct Parser<Templates = NoCompute, Masks = NoCompute> {
+pub struct Parser<Templates = NoCompute, Masks = NoCompute, Types = NoCompute, Labels = NoCompute> {
     threshold: f32,
     special_whites: Vec<Regex>,
     special_blacks: Vec<Regex>,
@@ -56,8 +60,11 @@ pub struct Parser<Templates = NoCompute, Masks = NoCompute> {
     filter_alphabetic: bool,
     filter_numeric: bool,
     filter_impure: bool,
+    labels: Vec<(Regex, String)>,
     compute_templates: PhantomData<Templates>,
     compute_mask: PhantomData<Masks>,
+    compute_types: PhantomData<Types>,
+    compute_labels: PhantomData<Labels>,
 }
 
 impl Default for Parser {
@@ -76,8 +83,11 @@ impl Parser {
             filter_alphabetic: true,
             filter_numeric: false,
             filter_impure: false,
+            labels: Default::default(),
             compute_templates: Default::default(),
             compute_mask: Default::default(),
+            compute_types: Default::default(),
+            compute_labels: Default::default(),
         }
     }
 
@@ -136,11 +146,11 @@ impl Parser {
     }
 }
 
-impl<T> Parser<NoCompute, T> {
+impl<T, Y, L> Parser<NoCompute, T, Y, L> {
     // Add templates computation to the output
     #[must_use]
-    pub fn compute_templates(self) -> Parser<Compute, T> {
-        Parser::<Compute, T> {
+    pub fn compute_templates(self) -> Parser<Compute, T, Y, L> {
+        Parser::<Compute, T, Y, L> {
             threshold: self.threshold,
             special_whites: self.special_whites,
             special_blacks: self.special_blacks,
@@ -148,17 +158,20 @@ impl<T> Parser<NoCompute, T> {
             filter_alphabetic: self.filter_alphabetic,
             filter_numeric: self.filter_numeric,
             filter_impure: self.filter_impure,
+            labels: self.labels,
             compute_templates: Default::default(),
             compute_mask: Default::default(),
+            compute_types: Default::default(),
+            compute_labels: Default::default(),
         }
     }
 }
 
-impl<T> Parser<T, NoCompute> {
+impl<T, Y, L> Parser<T, NoCompute, Y, L> {
     // Add parameter mask computation to the output
     #[must_use]
-    pub fn compute_masks(self) -> Parser<T, Compute> {
-        Parser::<T, Compute> {
+    pub fn compute_masks(self) -> Parser<T, Compute, Y, L> {
+        Parser::<T, Compute, Y, L> {
             threshold: self.threshold,
             special_whites: self.special_whites,
             special_blacks: self.special_blacks,
@@ -166,8 +179,59 @@ impl<T> Parser<T, NoCompute> {
             filter_alphabetic: self.filter_alphabetic,
             filter_numeric: self.filter_numeric,
             filter_impure: self.filter_impure,
+            labels: self.labels,
             compute_templates: Default::default(),
             compute_mask: Default::default(),
+            compute_types: Default::default(),
+            compute_labels: Default::default(),
+        }
+    }
+}
+
+impl<T, M, L> Parser<T, M, NoCompute, L> {
+    // Add typed-parameter computation to the output
+    #[must_use]
+    pub fn compute_types(self) -> Parser<T, M, Compute, L> {
+        Parser::<T, M, Compute, L> {
+            threshold: self.threshold,
+            special_whites: self.special_whites,
+            special_blacks: self.special_blacks,
+            symbols: self.symbols,
+            filter_alphabetic: self.filter_alphabetic,
+            filter_numeric: self.filter_numeric,
+            filter_impure: self.filter_impure,
+            labels: self.labels,
+            compute_templates: Default::default(),
+            compute_mask: Default::default(),
+            compute_types: Default::default(),
+            compute_labels: Default::default(),
+        }
+    }
+}
+
+impl<T, M, Y> Parser<T, M, Y, NoCompute> {
+    /// Sets `value` as the label dictionary and adds label attachment to the
+    /// output. Requires `Templates` to already be computed (via
+    /// [`Parser::compute_templates`]): once templates are known, each
+    /// cluster's template string(s) are tested against every `(Regex,
+    /// String)` rule in `value`, and every rule whose regex matches at least
+    /// one of the cluster's templates contributes its label, so a cluster can
+    /// carry several labels (or none).
+    #[must_use]
+    pub fn with_labels(self, value: Vec<(Regex, String)>) -> Parser<T, M, Y, Compute> {
+        Parser::<T, M, Y, Compute> {
+            threshold: self.threshold,
+            special_whites: self.special_whites,
+            special_blacks: self.special_blacks,
+            symbols: self.symbols,
+            filter_alphabetic: self.filter_alphabetic,
+            filter_numeric: self.filter_numeric,
+            filter_impure: self.filter_impure,
+            labels: value,
+            compute_templates: Default::default(),
+            compute_mask: Default::default(),
+            compute_types: Default::default(),
+            compute_labels: Default::default(),
         }
     }
 }
@@ -199,6 +263,104 @@ impl Parser<NoCompute, NoCompute> {
             });
         clus
     }
+
+    /// Computes the global token-interdependency structure across `messages`
+    /// and renders it as a Graphviz `digraph`: one node per distinct token,
+    /// labeled with its occurrence count, and one directed edge `eve -> con`
+    /// for every ordered pair whose [`TokenRecord::dependency`] clears
+    /// `self.threshold`, labeled with that dependency value. `dependency` is
+    /// asymmetric, so both directions are emitted whenever both clear the
+    /// threshold — this is exactly the per-message structure
+    /// `group_by_anchor_tokens` builds, so the DOT output doubles as a way to
+    /// see why a set of tokens was (or wasn't) chosen as an anchor.
+    pub fn export_dependency_graph<Message: AsRef<str> + Sync>(self, messages: &[Message]) -> String {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+
+        let mut dot = String::from("digraph dependency {\n");
+        for tok in idep.tokens() {
+            let count = idep.occurence(tok).unwrap_or(0);
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{} ({count})\"];\n",
+                dot_escape(tok),
+                dot_escape(tok)
+            ));
+        }
+        for (tok1, tok2) in idep.tokens().tuple_combinations() {
+            if let Some(dep) = idep.dependency(tok1, tok2) {
+                if dep > self.threshold {
+                    dot.push_str(&format!(
+                        "    \"{}\" -> \"{}\" [label=\"{dep:.2}\", weight=\"{dep:.2}\"];\n",
+                        dot_escape(tok1),
+                        dot_escape(tok2)
+                    ));
+                }
+            }
+            if let Some(dep) = idep.dependency(tok2, tok1) {
+                if dep > self.threshold {
+                    dot.push_str(&format!(
+                        "    \"{}\" -> \"{}\" [label=\"{dep:.2}\", weight=\"{dep:.2}\"];\n",
+                        dot_escape(tok2),
+                        dot_escape(tok1)
+                    ));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Runs the normal pipeline once over `messages` and freezes the result
+    /// into a [`Matcher`]: the trained token-dependency statistics,
+    /// tokenizer/filter config, threshold, and anchor-token-set-to-cluster-id
+    /// map are all retained, so later messages can be assigned a cluster id
+    /// via `Matcher::assign`/`Matcher::assign_or_insert` without reprocessing
+    /// the whole batch, and the trained state can be saved and reloaded.
+    pub fn into_matcher<Message: AsRef<str> + Sync>(self, messages: &[Message]) -> Matcher {
+        let special_white_patterns = self
+            .special_whites
+            .iter()
+            .map(|regex| regex.as_str().to_owned())
+            .collect();
+        let special_black_patterns = self
+            .special_blacks
+            .iter()
+            .map(|regex| regex.as_str().to_owned())
+            .collect();
+        let symbols = self.symbols.iter().copied().collect();
+
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let cmap = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+
+        let clusters = cmap
+            .into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .map(|(cid, (anchor_toks, _))| (anchor_toks, cid));
+
+        Matcher::new(
+            idep.to_owned(),
+            special_white_patterns,
+            special_black_patterns,
+            symbols,
+            self.threshold,
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+            clusters,
+        )
+    }
 }
 
 impl Parser<Compute, NoCompute> {
@@ -223,7 +385,7 @@ impl Parser<Compute, NoCompute> {
         let mut clus = vec![None; messages.len()];
         let mut temps = vec![HashSet::default(); cmap.len()];
         let tokenizer =
-            tokenizer.new_with_symbols("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~".chars().collect());
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
         cmap.into_iter()
             .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
             .enumerate()
@@ -276,7 +438,7 @@ impl Parser<NoCompute, Compute> {
         let mut clus = vec![None; messages.len()];
         let mut masks = HashMap::new();
         let tokenizer =
-            tokenizer.new_with_symbols("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~".chars().collect());
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
         cmap.into_iter()
             .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
             .enumerate()
@@ -330,7 +492,7 @@ impl Parser<Compute, Compute> {
         let mut temps = vec![HashSet::default(); groups.len()];
         let mut masks = HashMap::new();
         let tokenizer =
-            tokenizer.new_with_symbols("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~".chars().collect());
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
         groups
             .into_iter()
             .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
@@ -369,6 +531,569 @@ impl Parser<Compute, Compute> {
     }
 }
 
+impl Parser<NoCompute, NoCompute, Compute> {
+    /// Parses the input `messages` and returns `Clusters` and `ParamTypes`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `ParamTypes`: A `Vec<Vec<(usize, ParamType)>>` aligned with `Clusters`, giving the inferred
+    ///   type of each parameter position observed within that cluster.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, ParamTypes) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let cmap = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut types = vec![Vec::new(); cmap.len()];
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        cmap.into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                types[cid] = param_types(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        (clus, types)
+    }
+}
+
+impl Parser<Compute, NoCompute, Compute> {
+    /// Parses the input `messages` and returns `Clusters`, `Templates`, and `ParamTypes`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `Templates`: A `Vec<HashSet<String>>` where each set of templates is aligned with the
+    ///   corresponding cluster ID in the `Clusters` vector.
+    ///
+    /// - `ParamTypes`: A `Vec<Vec<(usize, ParamType)>>` aligned with `Clusters`, giving the inferred
+    ///   type of each parameter position observed within that cluster.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, Templates, ParamTypes) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let cmap = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut temps = vec![HashSet::default(); cmap.len()];
+        let mut types = vec![Vec::new(); cmap.len()];
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        cmap.into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                temps[cid] = templates(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                types[cid] = param_types(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        (
+            clus,
+            temps
+                .into_iter()
+                .map(|map| map.into_iter().collect())
+                .collect(),
+            types,
+        )
+    }
+}
+
+impl Parser<NoCompute, Compute, Compute> {
+    /// Parses the input `messages` and returns `Clusters`, `Masks`, and `ParamTypes`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `Masks`: A table mapping each message to its parameter masks.
+    ///
+    /// - `ParamTypes`: A `Vec<Vec<(usize, ParamType)>>` aligned with `Clusters`, giving the inferred
+    ///   type of each parameter position observed within that cluster.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, Masks, ParamTypes) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let cmap = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut masks = HashMap::new();
+        let mut types = vec![Vec::new(); cmap.len()];
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        cmap.into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                masks.extend(parameter_masks(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                ));
+                types[cid] = param_types(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        (clus, masks.into_iter().collect(), types)
+    }
+}
+
+impl Parser<Compute, Compute, Compute> {
+    /// Parses the input `messages` and returns `Clusters`, `Templates`, `Masks`, and `ParamTypes`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `Templates`: A `Vec<HashSet<String>>` where each set of templates is aligned with the
+    ///   corresponding cluster ID in the `Clusters` vector.
+    ///
+    /// - `Masks`: A table mapping each message to its parameter masks.
+    ///
+    /// - `ParamTypes`: A `Vec<Vec<(usize, ParamType)>>` aligned with `Clusters`, giving the inferred
+    ///   type of each parameter position observed within that cluster.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, Templates, Masks, ParamTypes) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let groups = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut temps = vec![HashSet::default(); groups.len()];
+        let mut masks = HashMap::new();
+        let mut types = vec![Vec::new(); groups.len()];
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        groups
+            .into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                temps[cid] = templates(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                masks.extend(parameter_masks(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                ));
+                types[cid] = param_types(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        (
+            clus,
+            temps
+                .into_iter()
+                .map(|map| map.into_iter().collect())
+                .collect(),
+            masks.into_iter().collect(),
+            types,
+        )
+    }
+}
+
+impl Parser<Compute, NoCompute, NoCompute, Compute> {
+    /// Parses the input `messages` and returns `Clusters`, `Templates`, and `Labels`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `Templates`: A `Vec<HashSet<String>>` where each set of templates is aligned with the
+    ///   corresponding cluster ID in the `Clusters` vector.
+    ///
+    /// - `Labels`: A `Vec<HashSet<String>>` aligned with `Clusters`, holding every label whose
+    ///   rule matched at least one of that cluster's templates.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, Templates, Labels) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let cmap = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut temps = vec![HashSet::default(); cmap.len()];
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        cmap.into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                temps[cid] = templates(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        let labs = label_clusters(&self.labels, &temps);
+        (
+            clus,
+            temps
+                .into_iter()
+                .map(|map| map.into_iter().collect())
+                .collect(),
+            labs,
+        )
+    }
+}
+
+impl Parser<Compute, NoCompute, Compute, Compute> {
+    /// Parses the input `messages` and returns `Clusters`, `Templates`, `ParamTypes`, and `Labels`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `Templates`: A `Vec<HashSet<String>>` where each set of templates is aligned with the
+    ///   corresponding cluster ID in the `Clusters` vector.
+    ///
+    /// - `ParamTypes`: A `Vec<Vec<(usize, ParamType)>>` aligned with `Clusters`, giving the inferred
+    ///   type of each parameter position observed within that cluster.
+    ///
+    /// - `Labels`: A `Vec<HashSet<String>>` aligned with `Clusters`, holding every label whose
+    ///   rule matched at least one of that cluster's templates.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, Templates, ParamTypes, Labels) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let cmap = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut temps = vec![HashSet::default(); cmap.len()];
+        let mut types = vec![Vec::new(); cmap.len()];
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        cmap.into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                temps[cid] = templates(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                types[cid] = param_types(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        let labs = label_clusters(&self.labels, &temps);
+        (
+            clus,
+            temps
+                .into_iter()
+                .map(|map| map.into_iter().collect())
+                .collect(),
+            types,
+            labs,
+        )
+    }
+}
+
+impl Parser<Compute, Compute, NoCompute, Compute> {
+    /// Parses the input `messages` and returns `Clusters`, `Templates`, `Masks`, and `Labels`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `Templates`: A `Vec<HashSet<String>>` where each set of templates is aligned with the
+    ///   corresponding cluster ID in the `Clusters` vector.
+    ///
+    /// - `Masks`: A table mapping each message to its parameter masks.
+    ///
+    /// - `Labels`: A `Vec<HashSet<String>>` aligned with `Clusters`, holding every label whose
+    ///   rule matched at least one of that cluster's templates.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, Templates, Masks, Labels) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let groups = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut temps = vec![HashSet::default(); groups.len()];
+        let mut masks = HashMap::new();
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        groups
+            .into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                temps[cid] = templates(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                masks.extend(parameter_masks(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                ));
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        let labs = label_clusters(&self.labels, &temps);
+        (
+            clus,
+            temps
+                .into_iter()
+                .map(|map| map.into_iter().collect())
+                .collect(),
+            masks.into_iter().collect(),
+            labs,
+        )
+    }
+}
+
+impl Parser<Compute, Compute, Compute, Compute> {
+    /// Parses the input `messages` and returns `Clusters`, `Templates`, `Masks`, `ParamTypes`, and `Labels`.
+    ///
+    /// - `Clusters`: A `Vec<Option<usize>>` representing potential cluster IDs. Each `Option<usize>`
+    ///   corresponds to the cluster ID of the message at the same index, or `None` if the message
+    ///   couldn't be parsed.
+    ///
+    /// - `Templates`: A `Vec<HashSet<String>>` where each set of templates is aligned with the
+    ///   corresponding cluster ID in the `Clusters` vector.
+    ///
+    /// - `Masks`: A table mapping each message to its parameter masks.
+    ///
+    /// - `ParamTypes`: A `Vec<Vec<(usize, ParamType)>>` aligned with `Clusters`, giving the inferred
+    ///   type of each parameter position observed within that cluster.
+    ///
+    /// - `Labels`: A `Vec<HashSet<String>>` aligned with `Clusters`, holding every label whose
+    ///   rule matched at least one of that cluster's templates.
+    ///
+    pub fn parse<Message: AsRef<str> + Sync>(
+        self,
+        messages: &[Message],
+    ) -> (Clusters, Templates, Masks, ParamTypes, Labels) {
+        let tokenizer = Tokenizer::new(self.special_whites, self.special_blacks, self.symbols);
+        let filter = StaticFilter::with(
+            self.filter_alphabetic,
+            self.filter_numeric,
+            self.filter_impure,
+        );
+        let idep = TokenRecord::new(messages, &tokenizer, &filter);
+        let groups = group_by_anchor_tokens(messages, &tokenizer, &idep, self.threshold);
+        let mut clus = vec![None; messages.len()];
+        let mut temps = vec![HashSet::default(); groups.len()];
+        let mut masks = HashMap::new();
+        let mut types = vec![Vec::new(); groups.len()];
+        let tokenizer =
+            tokenizer.new_with_symbols(TEMPLATE_SYMBOLS.chars().collect());
+        groups
+            .into_iter()
+            .filter(|(anchor_toks, _)| !anchor_toks.is_empty())
+            .enumerate()
+            .for_each(|(cid, (_, indices))| {
+                let stok = shared_slices(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    self.filter_alphabetic,
+                    self.filter_numeric,
+                    self.filter_impure,
+                );
+                temps[cid] = templates(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                masks.extend(parameter_masks(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                ));
+                types[cid] = param_types(
+                    indices.iter().cloned().map(|idx| messages[idx].as_ref()),
+                    &tokenizer,
+                    &stok,
+                );
+                for idx in indices {
+                    clus[idx] = Some(cid);
+                }
+            });
+
+        let labs = label_clusters(&self.labels, &temps);
+        (
+            clus,
+            temps
+                .into_iter()
+                .map(|map| map.into_iter().collect())
+                .collect(),
+            masks.into_iter().collect(),
+            types,
+            labs,
+        )
+    }
+}
+
+/// Tests every cluster's template string(s) against every `(Regex, String)`
+/// rule, returning the set of labels whose rule matched at least one
+/// template for that cluster (empty if none matched), aligned by cluster id
+/// with `temps`.
+fn label_clusters(rules: &[(Regex, String)], temps: &[HashSet<String>]) -> Labels {
+    temps
+        .iter()
+        .map(|cluster_temps| {
+            rules
+                .iter()
+                .filter(|(re, _)| cluster_temps.iter().any(|t| re.is_match(t).unwrap_or(false)))
+                .map(|(_, label)| label.clone())
+                .collect()
+        })
+        .collect()
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 fn group_by_anchor_tokens<'a, T: AsRef<str> + Sync>(
     messages: &'a [T],
     tokenizer: &Tokenizer,
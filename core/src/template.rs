@@ -1,9 +1,14 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use fancy_regex::Regex;
 use hashbrown::{HashMap, HashSet};
 
 use rayon::prelude::*;
 
 use crate::{
-    tokenizer::{Token, Tokenizer},
+    misc::{compile_placeholder_rules, softmax2},
+    tokenizer::{Span, Token, Tokenizer},
     traits::Tokenize,
 };
 
@@ -77,6 +82,268 @@ pub fn templates<'a, Iter: Iterator<Item = &'a str> + Send>(
         .collect()
 }
 
+/// Default [`templates_typed`] rules covering common log value shapes:
+/// UUIDs, URLs, IPv4/IPv6 addresses, integer/float numbers, hex blobs,
+/// file paths, and quoted strings. Checked in this order, most specific
+/// first, so e.g. a UUID is never misclassified as a hex blob and an
+/// all-digit number is `<NUM>` rather than `<HEX>`.
+pub fn default_placeholder_rules() -> Vec<(Regex, String)> {
+    compile_placeholder_rules(
+        [
+            (
+                "UUID",
+                r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+            ),
+            ("URL", r"^[a-zA-Z][a-zA-Z0-9+.-]*://\S+$"),
+            (
+                "IP",
+                r"^(?:\d{1,3}\.){3}\d{1,3}$|^[0-9a-fA-F]*:[0-9a-fA-F:]+$",
+            ),
+            ("NUM", r"^-?\d+(?:\.\d+)?$"),
+            ("HEX", r"^(?:0[xX])?[0-9a-fA-F]{4,}$"),
+            ("PATH", r"^(?:/|[A-Za-z]:\\|\.{1,2}/)\S*$"),
+            ("STR", r#"^(['"]).*\1$"#),
+        ]
+        .map(|(label, pattern): (&str, &str)| (label.to_owned(), pattern)),
+    )
+}
+
+/// Like [`templates`], but instead of a blanket `<*>` for every variable
+/// position, classifies the substring each contiguous non-common run
+/// matched against `rules` (first match wins, e.g. those built by
+/// [`default_placeholder_rules`]) and emits a typed placeholder such as
+/// `<NUM>` or `<IP>`, falling back to `<*>` when nothing matches. Typed
+/// templates avoid false merges, e.g. an IP column and a numeric column
+/// collapsing onto the same wildcard.
+pub fn templates_typed<'a, Iter: Iterator<Item = &'a str> + Send>(
+    iter: Iter,
+    tokenizer: &Tokenizer,
+    common_slices: &HashSet<&'a str>,
+    rules: &[(Regex, String)],
+) -> HashSet<String> {
+    iter.par_bridge()
+        .map(|msg| typed_template(msg, tokenizer, common_slices, rules))
+        .fold_with(HashSet::new(), |mut temp_set, temp| {
+            temp_set.insert(temp);
+            temp_set
+        })
+        .reduce(Default::default, |s1, s2| {
+            let (mut larger, smaller) = if s1.len() > s2.len() {
+                (s1, s2)
+            } else {
+                (s2, s1)
+            };
+            larger.extend(smaller);
+            larger
+        })
+        .into_iter()
+        .collect()
+}
+
+fn typed_template(
+    msg: &str,
+    tokenizer: &Tokenizer,
+    common_slices: &HashSet<&str>,
+    rules: &[(Regex, String)],
+) -> String {
+    let mut temp = String::with_capacity(msg.len());
+    let mut run: Option<Span> = None;
+    for (tok, span) in tokenizer.tokenize_spanned(msg) {
+        if common_slices.contains(tok.as_str()) {
+            flush_run(&mut temp, &mut run, msg, rules);
+            temp.push_str(tok.as_str());
+        } else {
+            run = Some(match run {
+                Some(prev) => Span {
+                    start: prev.start,
+                    end: span.end,
+                },
+                None => span,
+            });
+        }
+    }
+    flush_run(&mut temp, &mut run, msg, rules);
+    temp
+}
+
+fn flush_run(temp: &mut String, run: &mut Option<Span>, msg: &str, rules: &[(Regex, String)]) {
+    if let Some(span) = run.take() {
+        temp.push_str(&placeholder_for(&msg[span.start..span.end], rules));
+    }
+}
+
+fn placeholder_for(slice: &str, rules: &[(Regex, String)]) -> String {
+    rules
+        .iter()
+        .find(|(pattern, _)| pattern.is_match(slice).unwrap_or(false))
+        .map(|(_, label)| format!("<{label}>"))
+        .unwrap_or_else(|| "<*>".to_owned())
+}
+
+/// Recovers the exact substring each placeholder in a mined template
+/// matched for `msg`. Mirrors the contiguous-run folding
+/// [`templates_typed`] does (every run of adjacent non-common tokens is one
+/// placeholder), but instead of emitting a placeholder token it returns the
+/// ordinal placeholder index paired with the `Span`-backed slice it spans,
+/// turning the crate from a template miner into a structured-field
+/// extractor. [`templates`] does not fold runs: it emits one `<*>` per
+/// non-common token, so its placeholder count differs from these indices
+/// whenever non-common tokens are adjacent.
+pub fn extract_parameters<'a>(
+    msg: &'a str,
+    tokenizer: &Tokenizer,
+    common_slices: &HashSet<&str>,
+) -> Vec<(usize, &'a str)> {
+    let mut params = Vec::new();
+    let mut placeholder_idx = 0;
+    let mut run: Option<Span> = None;
+    for (tok, span) in tokenizer.tokenize_spanned(msg) {
+        if common_slices.contains(tok.as_str()) {
+            if let Some(run) = run.take() {
+                params.push((placeholder_idx, &msg[run.start..run.end]));
+                placeholder_idx += 1;
+            }
+        } else {
+            run = Some(match run {
+                Some(prev) => Span {
+                    start: prev.start,
+                    end: span.end,
+                },
+                None => span,
+            });
+        }
+    }
+    if let Some(run) = run {
+        params.push((placeholder_idx, &msg[run.start..run.end]));
+    }
+    params
+}
+
+/// Inferred type of a parameter position, widened across every value
+/// observed for it within a cluster. See [`param_types`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    /// Matched one of [`TIMESTAMP_FORMATS`]; holds that format's label.
+    Timestamp(String),
+    IpAddr,
+}
+
+/// `(label, pattern)` pairs describing a handful of common timestamp
+/// layouts, checked in this order, most specific first. Unlike
+/// [`default_placeholder_rules`] these aren't used to rewrite a template —
+/// only to recognize the *shape* of a value for [`classify_value`], so the
+/// pattern doesn't need to fully validate the timestamp (e.g. month/day
+/// ranges).
+const TIMESTAMP_FORMATS: &[(&str, &str)] = &[
+    (
+        "%Y-%m-%dT%H:%M:%S%.fZ",
+        r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?Z$",
+    ),
+    (
+        "%Y-%m-%dT%H:%M:%S%z",
+        r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?[+-]\d{2}:?\d{2}$",
+    ),
+    (
+        "%Y-%m-%d %H:%M:%S",
+        r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?$",
+    ),
+    ("%Y-%m-%d", r"^\d{4}-\d{2}-\d{2}$"),
+    (
+        "%d/%b/%Y:%H:%M:%S %z",
+        r"^\d{2}/[A-Za-z]{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}$",
+    ),
+];
+
+/// Infers the most specific [`ParamType`] a single observed `value` satisfies:
+/// `i64` parse, then `f64`, then case-insensitive `true`/`false`, then one of
+/// [`TIMESTAMP_FORMATS`], then an IPv4/IPv6 address, falling back to `Bytes`.
+/// An empty value is always `Bytes`.
+fn classify_value(value: &str) -> ParamType {
+    if value.is_empty() {
+        return ParamType::Bytes;
+    }
+    if value.parse::<i64>().is_ok() {
+        return ParamType::Integer;
+    }
+    if value.parse::<f64>().is_ok() {
+        return ParamType::Float;
+    }
+    if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+        return ParamType::Boolean;
+    }
+    for (label, pattern) in TIMESTAMP_FORMATS {
+        if Regex::new(pattern).unwrap().is_match(value).unwrap_or(false) {
+            return ParamType::Timestamp((*label).to_owned());
+        }
+    }
+    if value.parse::<std::net::IpAddr>().is_ok() {
+        return ParamType::IpAddr;
+    }
+    ParamType::Bytes
+}
+
+/// Widens two observed [`ParamType`]s for the same position to the most
+/// specific type both satisfy: identical types are kept as-is, `Integer` and
+/// `Float` widen to `Float`, and anything else incompatible (including two
+/// different `Timestamp` formats) widens to `Bytes`.
+fn widen_param_type(a: ParamType, b: ParamType) -> ParamType {
+    if a == b {
+        return a;
+    }
+    match (a, b) {
+        (ParamType::Integer, ParamType::Float) | (ParamType::Float, ParamType::Integer) => {
+            ParamType::Float
+        }
+        _ => ParamType::Bytes,
+    }
+}
+
+/// For each parameter position produced by [`extract_parameters`] across
+/// every message in `iter`, infers the [`ParamType`] that every observed
+/// value at that position satisfies (see [`classify_value`]/[`widen_param_type`]),
+/// so downstream consumers get structured values instead of just a string
+/// mask. A position with a single observed value is still typed, and an
+/// empty observed value is `Bytes`. Only positions observed in at least one
+/// message appear in the result.
+pub fn param_types<'a, Iter: Iterator<Item = &'a str> + Send>(
+    iter: Iter,
+    tokenizer: &Tokenizer,
+    common_slices: &HashSet<&'a str>,
+) -> Vec<(usize, ParamType)> {
+    let by_position = iter
+        .par_bridge()
+        .map(|msg| extract_parameters(msg, tokenizer, common_slices))
+        .fold_with(HashMap::<usize, ParamType>::new(), |mut acc, params| {
+            for (pos, slice) in params {
+                let ty = classify_value(slice);
+                acc.entry(pos)
+                    .and_modify(|existing| {
+                        *existing = widen_param_type(existing.clone(), ty.clone())
+                    })
+                    .or_insert(ty);
+            }
+            acc
+        })
+        .reduce(HashMap::new, |mut m1, m2| {
+            for (pos, ty) in m2 {
+                m1.entry(pos)
+                    .and_modify(|existing| {
+                        *existing = widen_param_type(existing.clone(), ty.clone())
+                    })
+                    .or_insert(ty);
+            }
+            m1
+        });
+
+    let mut result = by_position.into_iter().collect::<Vec<_>>();
+    result.sort_by_key(|(pos, _)| *pos);
+    result
+}
+
 pub fn parameter_masks<'a, Iter: Iterator<Item = &'a str> + Send>(
     iter: Iter,
     tokenizer: &Tokenizer,
@@ -146,6 +413,152 @@ pub fn parameter_masks<'a, Iter: Iterator<Item = &'a str> + Send>(
         .collect()
 }
 
+/// Label assigned to a single [`Token`] by [`parameter_masks_beam`]: `Static`
+/// becomes mask bit `0`, `Parameter` becomes mask bit `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Static,
+    Parameter,
+}
+
+#[derive(Debug, Clone)]
+struct Sequence {
+    labels: Vec<Label>,
+    log_prob: f32,
+}
+
+impl PartialEq for Sequence {
+    fn eq(&self, other: &Self) -> bool {
+        self.log_prob == other.log_prob
+    }
+}
+
+impl Eq for Sequence {}
+
+impl PartialOrd for Sequence {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Sequence {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.log_prob.total_cmp(&other.log_prob)
+    }
+}
+
+/// Beam-search alternative to [`parameter_masks`]. Instead of a single greedy
+/// left-to-right pass driven by a `should_parameterize` flag, this treats
+/// per-token labeling (static vs. parameter) as a sequence-scoring problem,
+/// inspired by maxent chunkers.
+///
+/// Each token's emission score comes from its `common_slices` membership (a
+/// proxy for corpus document frequency: common tokens score toward static,
+/// rare ones toward parameter), turned into label probabilities via
+/// [`softmax2`]. A beam of partial [`Sequence`]s is kept, ranked by
+/// cumulative log-probability; at every step the top `beam_width` sequences
+/// are expanded with both labels, scored by emission plus a transition term
+/// that forces `Static` on `Whitespace` (resetting continuation), forces
+/// `Parameter`/`Static` on `SpecialBlack`/`SpecialWhite`, and rewards
+/// repeating the previous label across adjacent non-whitespace tokens so a
+/// variable run inside a "word" stays coherent. The single best sequence is
+/// then expanded to per-character `0`/`1` using token byte lengths, exactly
+/// as [`parameter_masks`] does.
+pub fn parameter_masks_beam<'a, Iter: Iterator<Item = &'a str> + Send>(
+    iter: Iter,
+    tokenizer: &Tokenizer,
+    common_slices: &HashSet<&'a str>,
+    beam_width: usize,
+) -> HashMap<String, String> {
+    iter.par_bridge()
+        .fold_with(HashMap::new(), |mut map, msg| {
+            let toks = tokenizer.tokenize(msg);
+            let mask = beam_search_mask(&toks, common_slices, beam_width.max(1));
+            map.insert(msg, mask);
+            map
+        })
+        .reduce(HashMap::new, |mut m1, m2| {
+            for (k, v) in m2 {
+                if !m1.contains_key(k) {
+                    m1.insert(k, v);
+                }
+            }
+            m1
+        })
+        .into_iter()
+        .map(|(k, v)| (k.to_owned(), v))
+        .collect()
+}
+
+const CONTINUATION_BONUS: f32 = 0.25;
+
+fn beam_search_mask(toks: &[Token], common_slices: &HashSet<&str>, beam_width: usize) -> String {
+    if toks.is_empty() {
+        return String::new();
+    }
+
+    let mut beam = vec![Sequence {
+        labels: Vec::new(),
+        log_prob: 0.0,
+    }];
+
+    for (idx, tok) in toks.iter().enumerate() {
+        let mut expanded = BinaryHeap::with_capacity(beam.len() * 2);
+        let prev_non_whitespace = idx > 0 && !matches!(toks[idx - 1], Token::Whitespace(_));
+        for seq in beam {
+            match tok {
+                Token::Whitespace(_) => expanded.push(push_label(seq, Label::Static, 0.0)),
+                Token::SpecialBlack(_) => expanded.push(push_label(seq, Label::Parameter, 0.0)),
+                Token::SpecialWhite(_) => expanded.push(push_label(seq, Label::Static, 0.0)),
+                _ => {
+                    let common = common_slices.contains(tok.as_str());
+                    let (static_logit, param_logit) = if common { (2.0, -2.0) } else { (-2.0, 2.0) };
+                    let (p_static, p_param) = softmax2(static_logit, param_logit);
+                    let prev_label = seq.labels.last().copied();
+
+                    let bonus = |label: Label| {
+                        if prev_non_whitespace && prev_label == Some(label) {
+                            CONTINUATION_BONUS
+                        } else {
+                            0.0
+                        }
+                    };
+                    expanded.push(push_label(
+                        seq.clone(),
+                        Label::Static,
+                        p_static.ln() + bonus(Label::Static),
+                    ));
+                    expanded.push(push_label(
+                        seq,
+                        Label::Parameter,
+                        p_param.ln() + bonus(Label::Parameter),
+                    ));
+                }
+            }
+        }
+        beam = (0..beam_width).map_while(|_| expanded.pop()).collect();
+    }
+
+    let best = beam.into_iter().max().expect("beam is never emptied");
+    let mut mask = String::with_capacity(toks.iter().map(|tok| tok.as_str().len()).sum());
+    for (tok, label) in toks.iter().zip(best.labels) {
+        let bit = match label {
+            Label::Static => '0',
+            Label::Parameter => '1',
+        };
+        for _ in 0..tok.as_str().len() {
+            mask.push(bit);
+        }
+    }
+    mask
+}
+
+fn push_label(mut seq: Sequence, label: Label, log_prob_delta: f32) -> Sequence {
+    seq.labels.push(label);
+    seq.log_prob += log_prob_delta;
+    seq
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,4 +592,49 @@ mod tests {
             println!("{v}");
         }
     }
+
+    #[test]
+    fn test_parameter_masks_beam() {
+        let msgs = ["The value is a", "The value is b"];
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), "".chars().collect());
+        let common_slices = shared_slices(msgs.into_iter(), &tokenizer, true, false, false);
+        let pm = parameter_masks_beam(msgs.into_iter(), &tokenizer, &common_slices, 4);
+        assert_eq!(pm.get("The value is a"), Some(&"00000000000001".to_owned()));
+        assert_eq!(pm.get("The value is b"), Some(&"00000000000001".to_owned()));
+    }
+
+    #[test]
+    fn test_extract_parameters() {
+        let msgs = ["The value is a x1 x2", "The value is b x1 x2"];
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), "".chars().collect());
+        let common_slices = shared_slices(msgs.into_iter(), &tokenizer, true, false, false);
+        let params = extract_parameters(msgs[0], &tokenizer, &common_slices);
+        assert_eq!(params, vec![(0, "a"), (1, "x1"), (2, "x2")]);
+    }
+
+    #[test]
+    fn test_templates_typed() {
+        let msgs = ["Connecting to 192.168.1.10", "Connecting to 10.0.0.1"];
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), "".chars().collect());
+        let common_slices = shared_slices(msgs.into_iter(), &tokenizer, true, false, true);
+        let rules = default_placeholder_rules();
+        let temps = templates_typed(msgs.into_iter(), &tokenizer, &common_slices, &rules);
+        assert_eq!(temps, HashSet::from(["Connecting to <IP>".to_owned()]));
+    }
+
+    #[test]
+    fn test_param_types() {
+        let msgs = [
+            "user id 42 from 192.168.1.10",
+            "user id 7 from 10.0.0.1",
+            "user id abc from 10.0.0.2",
+        ];
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), "".chars().collect());
+        let common_slices = shared_slices(msgs.into_iter(), &tokenizer, true, false, false);
+        let types = param_types(msgs.into_iter(), &tokenizer, &common_slices);
+        assert_eq!(
+            types,
+            vec![(0, ParamType::Bytes), (1, ParamType::IpAddr)]
+        );
+    }
 }
@@ -1,9 +1,12 @@
+use std::collections::BTreeSet;
+
 use hashbrown::{HashMap, HashSet};
 
 use crate::traits::{TokenFilter, Tokenize};
 use itertools::Itertools;
 
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 struct TokenPair<'a>(&'a str, &'a str);
@@ -114,6 +117,13 @@ impl<'a> TokenRecord<'a> {
         self.soc.get(tok.as_ref()).copied()
     }
 
+    /// Iterates every distinct token tracked across the corpus this record
+    /// was built from, for callers (e.g. dependency-graph export) that need
+    /// every node rather than a specific token's or pair's statistics.
+    pub fn tokens(&self) -> impl Iterator<Item = &'a str> + Clone + '_ {
+        self.soc.keys().copied()
+    }
+
     #[allow(dead_code)]
     pub fn coocurence(&self, tok1: impl AsRef<str>, tok2: impl AsRef<str>) -> Option<u32> {
         self.poc
@@ -126,6 +136,65 @@ impl<'a> TokenRecord<'a> {
         let single = *self.soc.get(eve)?;
         Some((double as f32) / (single as f32))
     }
+
+    /// Clones the occurrence tables into an owned, serializable model.
+    pub fn to_owned(&self) -> OwnedTokenRecord {
+        OwnedTokenRecord {
+            soc: self
+                .soc
+                .iter()
+                .map(|(tok, count)| (tok.to_string(), *count))
+                .collect(),
+            poc: self
+                .poc
+                .iter()
+                .map(|(pair, count)| {
+                    (
+                        BTreeSet::from([pair.0.to_string(), pair.1.to_string()]),
+                        *count,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Owned counterpart of [`TokenRecord`]. Where `TokenRecord` borrows `&str`
+/// slices out of the message buffer it was trained on, `OwnedTokenRecord`
+/// holds its own `String`s, so it can outlive that buffer and be
+/// (de)serialized to train a dependency model once and reuse it across runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OwnedTokenRecord {
+    soc: std::collections::HashMap<String, u32>,
+    poc: std::collections::HashMap<BTreeSet<String>, u32>,
+}
+
+impl OwnedTokenRecord {
+    pub fn occurence(&self, tok: impl AsRef<str>) -> Option<u32> {
+        self.soc.get(tok.as_ref()).copied()
+    }
+
+    /// Iterates every distinct token tracked by this record.
+    pub fn tokens(&self) -> impl Iterator<Item = &str> + '_ {
+        self.soc.keys().map(String::as_str)
+    }
+
+    pub fn dependency(&self, eve: &str, con: &str) -> Option<f32> {
+        let double = *self
+            .poc
+            .get(&BTreeSet::from([eve.to_owned(), con.to_owned()]))?;
+        let single = *self.soc.get(eve)?;
+        Some((double as f32) / (single as f32))
+    }
+
+    pub fn coocurence(&self, tok1: impl AsRef<str>, tok2: impl AsRef<str>) -> Option<u32> {
+        self.poc
+            .get(&BTreeSet::from([
+                tok1.as_ref().to_owned(),
+                tok2.as_ref().to_owned(),
+            ]))
+            .copied()
+    }
 }
 
 #[cfg(test)]
@@ -4,6 +4,17 @@ use fancy_regex::Regex;
 
 use crate::traits::Tokenize;
 
+/// Symbol set [`crate::parser::Parser`] tokenizes messages with when it
+/// builds templates and masks, independent of whatever `symbols` a caller
+/// configured for the initial parse, so that consumers (like `Matcher`) can
+/// re-tokenize both rendered template text and new messages identically by
+/// using this same constant for both. Deliberately excludes `<`, `*`, and `>`, the characters that
+/// make up the rendered `<*>` placeholder itself: if they were treated as
+/// symbols, re-tokenizing a rendered template would shatter `<*>` into
+/// three single-character tokens instead of recovering it as one atomic
+/// placeholder.
+pub const TEMPLATE_SYMBOLS: &str = "!\"#$%&'()+,-./:;=?@[\\]^_`{|}~";
+
 pub struct Tokenizer {
     special_whites: Vec<Regex>,
     special_blacks: Vec<Regex>,
@@ -30,7 +41,38 @@ impl Tokenize for Tokenizer {
     }
 }
 
+/// Byte-offset span of a token within the message it was produced from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    fn of(base: &str, slice: &str) -> Self {
+        let start = slice.as_ptr() as usize - base.as_ptr() as usize;
+        Span {
+            start,
+            end: start + slice.len(),
+        }
+    }
+}
+
 impl Tokenizer {
+    /// Like [`Tokenize::tokenize`], but pairs every token with its byte-offset
+    /// [`Span`] within `msg`, so callers can recover which substring of the
+    /// original message a token (and, downstream, a `<*>` placeholder)
+    /// matched.
+    pub fn tokenize_spanned<'a>(&self, msg: &'a str) -> Vec<(Token<'a>, Span)> {
+        self.tokenize(msg)
+            .into_iter()
+            .map(|tok| {
+                let span = Span::of(msg, tok.as_str());
+                (tok, span)
+            })
+            .collect()
+    }
+
     pub fn new(
         special_whites: Vec<Regex>,
         special_blacks: Vec<Regex>,
@@ -1,18 +1,74 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
 
-use crate::tokenizer::{Token, Tokenizer};
+use crate::tokenizer::{default_token_filter, Token, Tokenizer};
 use itertools::Itertools;
 use petgraph::{algo::kosaraju_scc, matrix_graph::MatrixGraph};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tipping_rs::softmax2;
 
 pub type TokenCombination<'a> = BTreeSet<&'a str>;
 pub type TokenOccurance<'a> = HashMap<TokenCombination<'a>, usize>;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Interdependency<'a> {
     token_occurance: TokenOccurance<'a>,
 }
 
+/// Owned counterpart of [`Interdependency`]. Where `Interdependency` borrows
+/// `&str` slices out of the message buffer it was trained on, `OwnedInterdependency`
+/// holds its own `String`s, so it can outlive that buffer and be (de)serialized to
+/// train a co-occurrence model once and reuse it across runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OwnedInterdependency {
+    token_occurance: HashMap<BTreeSet<String>, usize>,
+}
+
+impl<'a> Interdependency<'a> {
+    /// Clones the co-occurrence table into an owned, serializable model.
+    pub fn to_owned(&self) -> OwnedInterdependency {
+        OwnedInterdependency {
+            token_occurance: self
+                .token_occurance
+                .iter()
+                .map(|(comb, count)| (comb.iter().map(|tok| tok.to_string()).collect(), *count))
+                .collect(),
+        }
+    }
+}
+
+impl OwnedInterdependency {
+    /// Borrows the owned co-occurrence table as a regular [`Interdependency`], so
+    /// that `dependency`, `contains`, `contains_pair`, and `key_tokens` can be
+    /// called against freshly tokenized messages without rebuilding the table.
+    pub fn as_ref(&self) -> Interdependency<'_> {
+        Interdependency {
+            token_occurance: self
+                .token_occurance
+                .iter()
+                .map(|(comb, count)| (comb.iter().map(|tok| tok.as_str()).collect(), *count))
+                .collect(),
+        }
+    }
+
+    pub fn dependency(&self, word: &str, condition: &str) -> f32 {
+        self.as_ref().dependency(word, condition)
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.as_ref().contains(word)
+    }
+
+    pub fn contains_pair(&self, word: &str, condition: &str) -> bool {
+        self.as_ref().contains_pair(word, condition)
+    }
+
+    pub fn key_tokens<'a>(&self, tokens: Vec<Token<'a>>, threshold: f32) -> BTreeSet<Token<'a>> {
+        self.as_ref().key_tokens(tokens, threshold)
+    }
+}
+
 impl<'a> Interdependency<'a> {
     pub fn with<S, F>(msgs: &'a [S], tokenizer: &Tokenizer, token_filter: F) -> Self
     where
@@ -63,6 +119,81 @@ impl<'a> Interdependency<'a> {
         }
     }
 
+    /// Tokenizes a single message and folds its unique filtered token set into the
+    /// occurrence table in place, mirroring the fold body of [`Interdependency::with`].
+    /// Lets a live dependency model be kept up to date as new log lines arrive,
+    /// without rebuilding the whole table from scratch.
+    pub fn add_message<S, F>(&mut self, msg: &'a S, tokenizer: &Tokenizer, token_filter: F)
+    where
+        S: AsRef<str> + ?Sized,
+        F: Fn(&Token) -> bool,
+    {
+        let toks = tokenizer
+            .tokenize(msg.as_ref())
+            .into_iter()
+            .unique()
+            .filter(token_filter)
+            .map(|tok| tok.as_str())
+            .collect::<HashSet<_>>();
+
+        for tok in &toks {
+            self.token_occurance
+                .entry([*tok].into())
+                .and_modify(|count| *count += 1)
+                .or_insert(1_usize);
+        }
+
+        for (tok1, tok2) in toks.iter().tuple_combinations() {
+            self.token_occurance
+                .entry([*tok1, *tok2].into())
+                .and_modify(|count| *count += 1)
+                .or_insert(1_usize);
+        }
+    }
+
+    /// Batched [`Interdependency::add_message`] over a slice of messages.
+    pub fn extend<S, F>(&mut self, msgs: &'a [S], tokenizer: &Tokenizer, token_filter: F)
+    where
+        S: AsRef<str>,
+        F: Fn(&Token) -> bool + Copy,
+    {
+        for msg in msgs {
+            self.add_message(msg, tokenizer, token_filter);
+        }
+    }
+
+    /// Reverses [`Interdependency::add_message`]: decrements the single- and
+    /// pair-combination counts for `msg`'s unique filtered tokens, pruning any
+    /// combination whose count drops to zero. Lets callers maintain a sliding
+    /// time window of logs instead of an ever-growing table.
+    pub fn remove_message<S, F>(&mut self, msg: &'a S, tokenizer: &Tokenizer, token_filter: F)
+    where
+        S: AsRef<str> + ?Sized,
+        F: Fn(&Token) -> bool,
+    {
+        let toks = tokenizer
+            .tokenize(msg.as_ref())
+            .into_iter()
+            .unique()
+            .filter(token_filter)
+            .map(|tok| tok.as_str())
+            .collect::<HashSet<_>>();
+
+        for tok in &toks {
+            if let Some(count) = self.token_occurance.get_mut(&BTreeSet::from([*tok])) {
+                *count = count.saturating_sub(1);
+            }
+        }
+
+        for (tok1, tok2) in toks.iter().tuple_combinations() {
+            if let Some(count) = self.token_occurance.get_mut(&BTreeSet::from([*tok1, *tok2])) {
+                *count = count.saturating_sub(1);
+            }
+        }
+
+        self.token_occurance.retain(|_, count| *count > 0);
+    }
+
     pub fn key_tokens(&self, tokens: Vec<Token<'a>>, threshold: f32) -> BTreeSet<Token<'_>> {
         let g = self.graph(&tokens, threshold);
         let scc = kosaraju_scc(&g);
@@ -93,6 +224,101 @@ impl<'a> Interdependency<'a> {
         key_nodes
     }
 
+    /// Soft alternative to [`Interdependency::key_tokens`]. Instead of cutting the
+    /// dependency graph at the largest strongly-connected component, this scores
+    /// candidate key-token *sets* with a beam search and returns the best-scoring
+    /// set along with its confidence (`log_prob`).
+    ///
+    /// For every ordered candidate token, the average `dependency` against the
+    /// tokens already kept in a partial sequence is treated as a "keep" score; the
+    /// competing keep/drop scores are normalized with a softmax and the beam is
+    /// pruned back to `beam_width` sequences, ranked by cumulative `log_prob`, at
+    /// every step. `max_len` caps how many tokens a single sequence may keep.
+    pub fn key_tokens_beam(
+        &self,
+        tokens: Vec<Token<'a>>,
+        threshold: f32,
+        beam_width: usize,
+        max_len: usize,
+    ) -> (BTreeSet<Token<'a>>, f32) {
+        let candidates = tokens
+            .iter()
+            .cloned()
+            .unique()
+            .filter(|tok| self.contains(tok.as_str()))
+            .collect::<Vec<_>>();
+
+        let mut beam = vec![Sequence {
+            tokens: Vec::new(),
+            log_prob: 0.0,
+        }];
+
+        for tok in &candidates {
+            let mut expanded = BinaryHeap::with_capacity(beam.len() * 2);
+            for seq in beam {
+                if seq.tokens.len() >= max_len {
+                    expanded.push(seq);
+                    continue;
+                }
+                let keep_score = if seq.tokens.is_empty() {
+                    threshold
+                } else {
+                    seq.tokens
+                        .iter()
+                        .map(|sel| self.dependency_or(tok.as_str(), sel.as_str(), threshold))
+                        .sum::<f32>()
+                        / seq.tokens.len() as f32
+                };
+                let (p_keep, p_drop) = softmax2(keep_score, 1.0 - keep_score);
+
+                let mut kept = seq.tokens.clone();
+                kept.push(tok.clone());
+                expanded.push(Sequence {
+                    tokens: kept,
+                    log_prob: seq.log_prob + p_keep.ln(),
+                });
+                expanded.push(Sequence {
+                    tokens: seq.tokens,
+                    log_prob: seq.log_prob + p_drop.ln(),
+                });
+            }
+            beam = (0..beam_width.max(1))
+                .map_while(|_| expanded.pop())
+                .collect();
+        }
+
+        let best = beam
+            .into_iter()
+            .max()
+            .unwrap_or(Sequence {
+                tokens: Vec::new(),
+                log_prob: f32::NEG_INFINITY,
+            });
+        let mut key_nodes = best.tokens.into_iter().collect::<BTreeSet<_>>();
+        for tok in tokens {
+            match tok {
+                Token::SpecialWhite(_) => {
+                    key_nodes.insert(tok);
+                }
+                Token::SpecialBlack(_) => {
+                    key_nodes.remove(&tok);
+                }
+                _ => (),
+            }
+        }
+        (key_nodes, best.log_prob)
+    }
+
+    /// Like [`Interdependency::dependency`], but returns `default` instead of
+    /// panicking when the pair has never been observed together.
+    fn dependency_or(&self, word: &str, condition: &str, default: f32) -> f32 {
+        if self.contains_pair(word, condition) {
+            self.dependency(word, condition)
+        } else {
+            default
+        }
+    }
+
     pub fn graph(&self, tokens: &[Token<'a>], threshold: f32) -> MatrixGraph<Token<'a>, ()> {
         let mut graph = MatrixGraph::with_capacity(tokens.len());
         let nodes = tokens
@@ -154,6 +380,33 @@ impl<'a> Interdependency<'a> {
     }
 }
 
+#[derive(Debug, Clone)]
+struct Sequence<'a> {
+    tokens: Vec<Token<'a>>,
+    log_prob: f32,
+}
+
+impl PartialEq for Sequence<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.log_prob == other.log_prob
+    }
+}
+
+impl Eq for Sequence<'_> {}
+
+impl PartialOrd for Sequence<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Sequence<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.log_prob.total_cmp(&other.log_prob)
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use regex::Regex;
@@ -171,18 +424,12 @@ mod tests {
             "Task 'attempt_1445182159119_0019_r_000000_1000' done.",
             // "Releasing unassigned and invalid container Container: [ContainerId: container_1445182159119_0013_01_000012, NodeId: MSRA-SA-41.fareast.corp.microsoft.com:10769, NodeHttpAddress: MSRA-SA-41.fareast.corp.microsoft.com:8042, Resource: <memory:1024, vCores:1>, Priority: 20, Token: Token { kind: ContainerToken, service: 10.190.173.170:10769 }, ]. RM may have assignment issues"
         ];
-        let idep = Interdependency::with(&line, &tokenizer, |tok| match tok {
-            Token::Alphabetic(_) => true,
-            Token::Numeric(_) => false,
-            Token::Symbolic(_) => false,
-            Token::Whitespace(_) => false,
-            Token::Impure(_) => false,
-            Token::SpecialWhite(_) => true,
-            Token::SpecialBlack(_) => false,
-        });
+        let idep = Interdependency::with(&line, &tokenizer, default_token_filter);
         // println!("{:?}", tokenizer.tokenize(line));
         println!("{:?}", idep);
         println!("{:?}", tokenizer.tokenize(line[0]));
-        assert_eq!(1, 0)
+        assert!(idep.contains("Task"));
+        assert!(idep.contains("done"));
+        assert!(!idep.contains("attempt_1445182159119_0019_r_000000_1000"));
     }
 }
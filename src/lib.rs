@@ -1,9 +1,23 @@
 use std::collections::{HashMap, HashSet};
 
 use fancy_regex::Regex;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 use tipping_rs::Tokenize;
 
+mod interdependency;
+mod template;
+mod tokenizer;
+
+/// Raised when a `special_whites`/`special_blacks` pattern fails to compile,
+/// instead of panicking (which would surface as a process-aborting
+/// `pyo3_runtime.PanicException`). Carries the offending pattern and the
+/// underlying `fancy_regex` error in its message, so callers can `try/except`
+/// a single recoverable error type.
+create_exception!(_lib_tipping, InvalidPatternError, PyException);
+
 #[pyclass]
 pub struct Tokenizer {
     internal: tipping_rs::Tokenizer,
@@ -12,26 +26,26 @@ pub struct Tokenizer {
 #[pymethods]
 impl Tokenizer {
     #[new]
-    pub fn new(special_whites: Vec<String>, special_blacks: Vec<String>, symbols: String) -> Self {
-        Self {
+    pub fn new(
+        special_whites: Vec<String>,
+        special_blacks: Vec<String>,
+        symbols: String,
+    ) -> PyResult<Self> {
+        let special_whites = special_whites
+            .into_iter()
+            .map(compile_regex)
+            .collect::<PyResult<Vec<_>>>()?;
+        let special_blacks = special_blacks
+            .into_iter()
+            .map(compile_regex)
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Self {
             internal: tipping_rs::Tokenizer::new(
-                special_whites
-                    .into_iter()
-                    .map(|pattern| {
-                        Regex::new(&pattern)
-                            .unwrap_or_else(|_| panic!("Unable to compile {pattern}"))
-                    })
-                    .collect::<Vec<_>>(),
-                special_blacks
-                    .into_iter()
-                    .map(|pattern| {
-                        Regex::new(&pattern)
-                            .unwrap_or_else(|_| panic!("Unable to compile {pattern}"))
-                    })
-                    .collect(),
+                special_whites,
+                special_blacks,
                 symbols.chars().collect(),
             ),
-        }
+        })
     }
 
     pub fn tokenize(&self, msg: String) -> Vec<String> {
@@ -43,8 +57,164 @@ impl Tokenizer {
     }
 }
 
+/// A single position within a fitted cluster's template: either a constant
+/// token every member message shared, or a wildcard (masked) position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum TemplateToken {
+    Constant(String),
+    Wildcard,
+}
+
+/// Tokenizes `template` (the `<*>`-placeholder string produced by
+/// `Parser::compute_templates`) back into a positional `TemplateToken`
+/// sequence, so a new message can be compared against it position by
+/// position.
+fn tokenize_template(template: &str, tokenizer: &tipping_rs::Tokenizer) -> Vec<TemplateToken> {
+    tokenizer
+        .tokenize(template)
+        .into_iter()
+        .map(|tok| match tok.as_str() {
+            "<*>" => TemplateToken::Wildcard,
+            slice => TemplateToken::Constant(slice.to_owned()),
+        })
+        .collect()
+}
+
+/// Online counterpart to `token_independency_clusters`: fits cluster
+/// templates once over a batch of messages, then classifies new messages
+/// against those templates without re-parsing the corpus. A new message is
+/// tokenized and compared against every cluster's template of the same
+/// token count; its score is the fraction of the template's constant
+/// positions it matches exactly (templates with no constant positions score
+/// 1.0). The message is assigned to the highest-scoring cluster whose score
+/// is at least `threshold`, or `None` if no cluster qualifies.
 #[pyclass]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Matcher {
+    threshold: f32,
+    special_white_patterns: Vec<String>,
+    special_black_patterns: Vec<String>,
+    clusters: Vec<Vec<TemplateToken>>,
+}
+
+#[pymethods]
+impl Matcher {
+    /// Parses `messages` with the given parser configuration, computing only
+    /// templates, and keeps one representative (the lexicographically
+    /// smallest, for determinism) template per cluster as the matching
+    /// signature for later `predict` calls.
+    ///
+    /// The rendered template text is re-tokenized with
+    /// [`tipping_rs::TEMPLATE_SYMBOLS`], not `config.symbols`: that's the
+    /// symbol set `Parser` itself used to build the template text, so it's
+    /// the only set that reproduces the same token boundaries (and keeps a
+    /// rendered `<*>` placeholder recoverable as a single wildcard token).
+    /// `predict`/`tokenizer` below re-tokenize incoming messages with the
+    /// same constant, so templates and messages stay comparable.
+    #[staticmethod]
+    fn fit(messages: Vec<String>, config: ParserConfig) -> PyResult<Self> {
+        let parser = config.build_parser()?.compute_templates();
+        let (_, templates) = parser.parse(&messages);
+
+        let tokenizer = tipping_rs::Tokenizer::new(
+            config.compiled_whites()?,
+            config.compiled_blacks()?,
+            tipping_rs::TEMPLATE_SYMBOLS.chars().collect(),
+        );
+        let clusters = templates
+            .iter()
+            .filter_map(|temps| temps.iter().min())
+            .map(|temp| tokenize_template(temp, &tokenizer))
+            .collect();
+
+        Ok(Self {
+            threshold: config.threshold,
+            special_white_patterns: config.special_whites,
+            special_black_patterns: config.special_blacks,
+            clusters,
+        })
+    }
+
+    /// Classifies each message against the fitted clusters, returning the
+    /// matching cluster id, or `None` for a message that doesn't clear
+    /// `threshold` against any of them.
+    fn predict(&self, messages: Vec<String>) -> PyResult<Vec<Option<usize>>> {
+        let tokenizer = self.tokenizer()?;
+        Ok(messages
+            .iter()
+            .map(|msg| self.predict_one(msg, &tokenizer))
+            .collect())
+    }
+
+    /// Serializes the fitted matcher, so it can be written to disk and
+    /// reloaded with `from_bytes` rather than re-fitted.
+    fn to_bytes(&self) -> PyResult<Vec<u8>> {
+        bincode::serialize(self)
+            .map_err(|err| PyValueError::new_err(format!("failed to serialize matcher: {err}")))
+    }
+
+    /// Reloads a matcher previously saved with `to_bytes`.
+    #[staticmethod]
+    fn from_bytes(bytes: Vec<u8>) -> PyResult<Self> {
+        bincode::deserialize(&bytes)
+            .map_err(|err| PyValueError::new_err(format!("failed to deserialize matcher: {err}")))
+    }
+}
+
+impl Matcher {
+    fn tokenizer(&self) -> PyResult<tipping_rs::Tokenizer> {
+        let special_whites = self
+            .special_white_patterns
+            .iter()
+            .map(compile_regex)
+            .collect::<PyResult<Vec<_>>>()?;
+        let special_blacks = self
+            .special_black_patterns
+            .iter()
+            .map(compile_regex)
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(tipping_rs::Tokenizer::new(
+            special_whites,
+            special_blacks,
+            tipping_rs::TEMPLATE_SYMBOLS.chars().collect(),
+        ))
+    }
+
+    fn predict_one(&self, msg: &str, tokenizer: &tipping_rs::Tokenizer) -> Option<usize> {
+        let tokens = tokenizer
+            .tokenize(msg)
+            .into_iter()
+            .map(|tok| tok.as_str())
+            .collect::<Vec<_>>();
+
+        self.clusters
+            .iter()
+            .enumerate()
+            .filter(|(_, template)| template.len() == tokens.len())
+            .filter_map(|(cid, template)| {
+                let (matched, total) = template.iter().zip(tokens.iter()).fold(
+                    (0usize, 0usize),
+                    |(matched, total), (tmpl_tok, tok)| match tmpl_tok {
+                        TemplateToken::Constant(slice) => {
+                            (matched + usize::from(slice.as_str() == *tok), total + 1)
+                        }
+                        TemplateToken::Wildcard => (matched, total),
+                    },
+                );
+                let score = if total == 0 {
+                    1.0
+                } else {
+                    matched as f32 / total as f32
+                };
+                (score >= self.threshold).then_some((cid, score))
+            })
+            .max_by(|(_, s1), (_, s2)| s1.total_cmp(s2))
+            .map(|(cid, _)| cid)
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct TokenFilter {
     alphabetic: bool,
     numeric: bool,
@@ -63,81 +233,272 @@ impl TokenFilter {
     }
 }
 
+/// Bundles every tokenizer/parser setting `token_independency_clusters` and
+/// `Matcher::fit` need (threshold, special-token patterns, symbols, token
+/// filter) into a single, serializable value, so a parsing profile can be
+/// built once, saved as TOML or JSON, checked into source control, and
+/// reused across runs instead of threading five loose parameters through
+/// every call.
+#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParserConfig {
+    threshold: f32,
+    special_whites: Vec<String>,
+    special_blacks: Vec<String>,
+    symbols: String,
+    filter: TokenFilter,
+}
+
+#[pymethods]
+impl ParserConfig {
+    #[new]
+    fn new(
+        threshold: f32,
+        special_whites: Vec<String>,
+        special_blacks: Vec<String>,
+        symbols: String,
+        filter: TokenFilter,
+    ) -> PyResult<Self> {
+        let config = Self {
+            threshold,
+            special_whites,
+            special_blacks,
+            symbols,
+            filter,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses a TOML-encoded config, validating every special-token pattern
+    /// eagerly so a malformed profile fails at load time.
+    #[staticmethod]
+    fn from_toml(src: String) -> PyResult<Self> {
+        let config: Self = toml::from_str(&src)
+            .map_err(|err| PyValueError::new_err(format!("invalid TOML config: {err}")))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses a JSON-encoded config, validating every special-token pattern
+    /// eagerly.
+    #[staticmethod]
+    fn from_json(src: String) -> PyResult<Self> {
+        let config: Self = serde_json::from_str(&src)
+            .map_err(|err| PyValueError::new_err(format!("invalid JSON config: {err}")))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads a config from `path`, dispatching to `from_toml`/`from_json` by
+    /// file extension.
+    #[staticmethod]
+    fn from_file(path: String) -> PyResult<Self> {
+        let content = std::fs::read_to_string(&path).map_err(|err| {
+            PyValueError::new_err(format!("failed to read config file '{path}': {err}"))
+        })?;
+        match std::path::Path::new(&path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+        {
+            Some("toml") => Self::from_toml(content),
+            Some("json") => Self::from_json(content),
+            Some(ext) => Err(PyValueError::new_err(format!(
+                "unsupported config file extension '{ext}'"
+            ))),
+            None => Err(PyValueError::new_err(format!(
+                "config file '{path}' has no extension"
+            ))),
+        }
+    }
+}
+
+impl ParserConfig {
+    fn validate(&self) -> PyResult<()> {
+        for pattern in self.special_whites.iter().chain(self.special_blacks.iter()) {
+            compile_regex(pattern)?;
+        }
+        Ok(())
+    }
+
+    fn compiled_whites(&self) -> PyResult<Vec<Regex>> {
+        self.special_whites.iter().map(compile_regex).collect()
+    }
+
+    fn compiled_blacks(&self) -> PyResult<Vec<Regex>> {
+        self.special_blacks.iter().map(compile_regex).collect()
+    }
+
+    fn build_parser(&self) -> PyResult<tipping_rs::Parser> {
+        Ok(tipping_rs::Parser::default()
+            .with_threshold(self.threshold)
+            .with_special_whites(self.compiled_whites()?)
+            .with_special_blacks(self.compiled_blacks()?)
+            .with_symbols(self.symbols.chars().collect())
+            .with_filter_alphabetic(self.filter.alphabetic)
+            .with_filter_numeric(self.filter.numeric)
+            .with_filter_impure(self.filter.impure))
+    }
+}
+
 #[pyclass]
 #[derive(Debug, Clone)]
 pub struct Computations {
     template: bool,
     mask: bool,
+    label: bool,
 }
 
 #[pymethods]
 impl Computations {
     #[new]
-    fn new(template: bool, mask: bool) -> Self {
-        Self { mask, template }
+    fn new(template: bool, mask: bool, label: bool) -> Self {
+        Self {
+            mask,
+            template,
+            label,
+        }
+    }
+}
+
+/// A user-supplied naming scheme: `(label, pattern)` pairs tested in order
+/// against a finalized cluster's template(s), the first match naming that
+/// cluster, so raw clusters can be turned into semantically meaningful
+/// categories (e.g. "auth_failure", "disk_error") in one pass.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct LabelRules {
+    rules: Vec<(String, Regex)>,
+}
+
+#[pymethods]
+impl LabelRules {
+    #[new]
+    fn new(rules: Vec<(String, String)>) -> PyResult<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|(label, pattern)| compile_regex(pattern).map(|re| (label, re)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Self { rules })
     }
 }
 
 type MessageClusters = Vec<Option<usize>>;
 type ParameterMasks = Vec<String>;
 type ClusterTemplates = Vec<HashSet<String>>;
+type MessageLabels = Vec<Option<String>>;
 
 #[pyfunction]
 fn token_independency_clusters(
     messages: Vec<String>,
-    threshold: f32,
-    special_whites: Vec<String>,
-    special_blacks: Vec<String>,
-    symbols: String,
-    filter: TokenFilter,
+    config: ParserConfig,
     comps: Computations,
-) -> PyResult<(MessageClusters, ParameterMasks, ClusterTemplates)> {
-    let special_blacks = special_blacks.into_iter().map(compile_regex).collect();
-    let special_whites = special_whites.into_iter().map(compile_regex).collect();
-    let symbols = symbols.chars().collect();
-
-    let parser = tipping_rs::Parser::default()
-        .with_threshold(threshold)
-        .with_special_whites(special_whites)
-        .with_special_blacks(special_blacks)
-        .with_symbols(symbols)
-        .with_filter_alphabetic(filter.alphabetic)
-        .with_filter_numeric(filter.numeric)
-        .with_filter_impure(filter.impure);
+    label_rules: Option<LabelRules>,
+) -> PyResult<(MessageClusters, ParameterMasks, ClusterTemplates, MessageLabels)> {
+    if comps.label && label_rules.is_none() {
+        return Err(PyValueError::new_err(
+            "label computation requested but no LabelRules provided",
+        ));
+    }
+    let rules = label_rules.map(|lr| lr.rules).unwrap_or_default();
+
+    let parser = config.build_parser()?;
     Ok(match comps {
         Computations {
             template: false,
             mask: false,
+            label: false,
         } => {
             let clusters = parser.parse(&messages);
-            (clusters, Default::default(), Default::default())
+            (
+                clusters,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            )
+        }
+        Computations {
+            template: false,
+            mask: false,
+            label: true,
+        } => {
+            let (clusters, templates) = parser.compute_templates().parse(&messages);
+            let labels = one_to_one_labels(&clusters, &label_clusters(&rules, &templates));
+            (clusters, Default::default(), Default::default(), labels)
         }
         Computations {
             template: false,
             mask: true,
+            label: false,
         } => {
             let (clusters, masks) = parser.compute_masks().parse(&messages);
             (
                 clusters,
                 one_to_one_masks(&messages, masks),
                 Default::default(),
+                Default::default(),
+            )
+        }
+        Computations {
+            template: false,
+            mask: true,
+            label: true,
+        } => {
+            let (clusters, templates, masks) =
+                parser.compute_masks().compute_templates().parse(&messages);
+            let labels = one_to_one_labels(&clusters, &label_clusters(&rules, &templates));
+            (
+                clusters,
+                one_to_one_masks(&messages, masks),
+                Default::default(),
+                labels,
             )
         }
         Computations {
             template: true,
             mask: false,
+            label: false,
         } => {
             let (clusters, templates) = parser.compute_templates().parse(&messages);
-            (clusters, Default::default(), templates)
+            (clusters, Default::default(), templates, Default::default())
+        }
+        Computations {
+            template: true,
+            mask: false,
+            label: true,
+        } => {
+            let (clusters, templates) = parser.compute_templates().parse(&messages);
+            let labels = one_to_one_labels(&clusters, &label_clusters(&rules, &templates));
+            (clusters, Default::default(), templates, labels)
+        }
+        Computations {
+            template: true,
+            mask: true,
+            label: false,
+        } => {
+            let (clusters, templates, masks) =
+                parser.compute_masks().compute_templates().parse(&messages);
+            (
+                clusters,
+                one_to_one_masks(&messages, masks),
+                templates,
+                Default::default(),
+            )
         }
-
         Computations {
             template: true,
             mask: true,
+            label: true,
         } => {
             let (clusters, templates, masks) =
                 parser.compute_masks().compute_templates().parse(&messages);
-            (clusters, one_to_one_masks(&messages, masks), templates)
+            let labels = one_to_one_labels(&clusters, &label_clusters(&rules, &templates));
+            (
+                clusters,
+                one_to_one_masks(&messages, masks),
+                templates,
+                labels,
+            )
         }
     })
 }
@@ -146,9 +507,14 @@ fn token_independency_clusters(
 #[pymodule]
 fn _lib_tipping(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(token_independency_clusters, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_key_tokens, m)?)?;
     m.add_class::<TokenFilter>()?;
+    m.add_class::<ParserConfig>()?;
     m.add_class::<Computations>()?;
     m.add_class::<Tokenizer>()?;
+    m.add_class::<Matcher>()?;
+    m.add_class::<LabelRules>()?;
+    m.add("InvalidPatternError", m.py().get_type::<InvalidPatternError>())?;
     Ok(())
 }
 
@@ -164,9 +530,98 @@ fn one_to_one_masks(messages: &[String], masks: HashMap<String, String>) -> Vec<
         .collect::<Vec<_>>()
 }
 
-fn compile_regex(re: impl AsRef<str>) -> Regex {
-    match Regex::new(re.as_ref()) {
-        Ok(regex) => regex,
-        Err(err) => panic!("Error: {}, Regex: {}", err, re.as_ref()),
-    }
+/// Names each finalized cluster by testing its template strings against
+/// `rules` in order, the first matching rule naming the cluster.
+fn label_clusters(
+    rules: &[(String, Regex)],
+    templates: &[HashSet<String>],
+) -> Vec<Option<String>> {
+    templates
+        .iter()
+        .map(|temps| {
+            rules
+                .iter()
+                .find(|(_, re)| temps.iter().any(|t| re.is_match(t).unwrap_or(false)))
+                .map(|(label, _)| label.clone())
+        })
+        .collect()
+}
+
+/// Expands per-cluster labels into a per-message `Vec`, mirroring
+/// `one_to_one_masks`.
+fn one_to_one_labels(
+    clusters: &MessageClusters,
+    cluster_labels: &[Option<String>],
+) -> Vec<Option<String>> {
+    clusters
+        .iter()
+        .map(|cid| cid.and_then(|cid| cluster_labels.get(cid).cloned().flatten()))
+        .collect()
+}
+
+fn compile_regex(re: impl AsRef<str>) -> PyResult<Regex> {
+    Regex::new(re.as_ref()).map_err(|err| {
+        InvalidPatternError::new_err(format!("invalid pattern '{}': {err}", re.as_ref()))
+    })
+}
+
+/// Extracts each message's key tokens with [`interdependency::Interdependency`]'s
+/// beam search ([`interdependency::Interdependency::key_tokens_beam`]): a
+/// co-occurrence model is built once over `messages`, then every message's
+/// own tokens are scored and cut against it at `threshold`, `beam_width`, and
+/// `max_len`. Each message's result also carries the beam's confidence
+/// (`log_prob`) for the returned key-token set.
+///
+/// This is the legacy `interdependency`/`tokenizer` key-token extraction path,
+/// kept distinct from [`token_independency_clusters`], which additionally groups
+/// messages into clusters and builds parameter masks.
+///
+/// `semantic_detection` turns on [`tokenizer::Tokenizer::with_semantic_detection`]
+/// so numeric-looking parameters (floats, hex, IP addresses, UUIDs, paths) are
+/// tokenized as their own classes instead of `Impure`.
+/// [`tokenizer::default_token_filter`] excludes those classes from the key
+/// tokens just as it excludes `Impure` tokens, so the returned key tokens are
+/// the same with the flag on or off.
+#[pyfunction]
+fn extract_key_tokens(
+    messages: Vec<String>,
+    special_whites: Vec<String>,
+    special_blacks: Vec<String>,
+    symbols: String,
+    threshold: f32,
+    semantic_detection: bool,
+    beam_width: usize,
+    max_len: usize,
+) -> PyResult<Vec<(Vec<String>, f32)>> {
+    let special_whites = special_whites
+        .iter()
+        .map(compile_plain_regex)
+        .collect::<PyResult<Vec<_>>>()?;
+    let special_blacks = special_blacks
+        .iter()
+        .map(compile_plain_regex)
+        .collect::<PyResult<Vec<_>>>()?;
+    let tokenizer = tokenizer::Tokenizer::new(special_whites, special_blacks, symbols.chars().collect())
+        .with_semantic_detection(semantic_detection);
+
+    let idep = interdependency::Interdependency::with(&messages, &tokenizer, tokenizer::default_token_filter);
+
+    Ok(messages
+        .iter()
+        .map(|msg| {
+            let toks = tokenizer.tokenize(msg);
+            let (key_tokens, log_prob) = idep.key_tokens_beam(toks, threshold, beam_width, max_len);
+            let key_tokens = key_tokens
+                .into_iter()
+                .map(|tok| tok.as_str().to_string())
+                .collect();
+            (key_tokens, log_prob)
+        })
+        .collect())
+}
+
+fn compile_plain_regex(re: impl AsRef<str>) -> PyResult<regex::Regex> {
+    regex::Regex::new(re.as_ref()).map_err(|err| {
+        InvalidPatternError::new_err(format!("invalid pattern '{}': {err}", re.as_ref()))
+    })
 }
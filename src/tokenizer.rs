@@ -2,10 +2,15 @@ use std::collections::HashSet;
 
 use regex::Regex;
 
+/// Alias kept for callers (e.g. [`crate::interdependency`]) that predate the
+/// `MessageToken` rename, so they can keep matching on `Token::Variant(..)`.
+pub type Token<'a> = MessageToken<'a>;
+
 pub struct Tokenizer {
     special_whites: Vec<Regex>,
     special_blacks: Vec<Regex>,
     symbols: HashSet<char>,
+    semantics: Option<SemanticPatterns>,
 }
 
 impl Tokenizer {
@@ -18,9 +23,20 @@ impl Tokenizer {
             special_whites,
             special_blacks,
             symbols,
+            semantics: None,
         }
     }
 
+    /// Enables detection of the built-in semantic token classes (`Float`, `Hex`,
+    /// `IpAddr`, `Uuid`, `Path`). When on, `Unrefined` slices that would otherwise
+    /// fall into `Impure` are classified into one of these instead. Off by default
+    /// so existing behavior is preserved.
+    #[must_use]
+    pub fn with_semantic_detection(mut self, value: bool) -> Self {
+        self.semantics = value.then(SemanticPatterns::new);
+        self
+    }
+
     pub fn tokenize<'a>(&self, msg: &'a str) -> Vec<MessageToken<'a>> {
         let mut tokens = Vec::new();
         for pre_token in self.pre_tokenize(msg) {
@@ -33,7 +49,7 @@ impl Tokenizer {
                     tokens.push(MessageToken::SpecialBlack(slice));
                 }
                 PreToken::Unrefined(slice) => {
-                    tokens.append(&mut split_token(slice, &self.symbols));
+                    tokens.append(&mut split_token(slice, &self.symbols, self.semantics.as_ref()));
                 }
             }
         }
@@ -118,7 +134,17 @@ fn split_special<'a, Special: Fn(&'a str) -> PreToken>(
     pre_tokens
 }
 
-fn split_token<'a>(msg: &'a str, symbols: &HashSet<char>) -> Vec<MessageToken<'a>> {
+fn split_token<'a>(
+    msg: &'a str,
+    symbols: &HashSet<char>,
+    semantics: Option<&SemanticPatterns>,
+) -> Vec<MessageToken<'a>> {
+    let classify = |slice: &'a str| {
+        let tok = MessageToken::with(slice, symbols);
+        semantics
+            .and_then(|patterns| patterns.reclassify(tok.clone()))
+            .unwrap_or(tok)
+    };
     let mut start_idx = 0;
     let mut toks = Vec::new();
     while let Some(end_idx) = msg[start_idx..]
@@ -126,17 +152,65 @@ fn split_token<'a>(msg: &'a str, symbols: &HashSet<char>) -> Vec<MessageToken<'a
         .map(|idx| idx + start_idx)
     {
         if start_idx < end_idx {
-            toks.push(MessageToken::with(&msg[start_idx..end_idx], symbols));
+            toks.push(classify(&msg[start_idx..end_idx]));
         }
-        toks.push(MessageToken::with(&msg[end_idx..end_idx + 1], symbols));
+        toks.push(classify(&msg[end_idx..end_idx + 1]));
         start_idx = end_idx + 1;
     }
     if start_idx < msg.len() {
-        toks.push(MessageToken::with(&msg[start_idx..], symbols));
+        toks.push(classify(&msg[start_idx..]));
     }
     toks
 }
 
+/// Compiled patterns backing [`Tokenizer::with_semantic_detection`] for the
+/// built-in semantic token classes.
+#[derive(Debug, Clone)]
+struct SemanticPatterns {
+    float: Regex,
+    hex: Regex,
+    ip_addr: Regex,
+    uuid: Regex,
+    path: Regex,
+}
+
+impl SemanticPatterns {
+    fn new() -> Self {
+        Self {
+            float: Regex::new(r"^-?\d+\.\d+$").unwrap(),
+            hex: Regex::new(r"^(?:0[xX])?[0-9a-fA-F]{4,}$").unwrap(),
+            ip_addr: Regex::new(r"^(?:\d{1,3}\.){3}\d{1,3}$").unwrap(),
+            uuid: Regex::new(
+                r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+            )
+            .unwrap(),
+            path: Regex::new(r"^/?(?:[\w.-]+/)+[\w.-]*$").unwrap(),
+        }
+    }
+
+    /// Reclassifies an `Impure` token into one of the semantic classes, if its
+    /// slice matches. Returns `None` for any other variant or for an `Impure`
+    /// slice that matches none of the patterns.
+    fn reclassify<'a>(&self, tok: MessageToken<'a>) -> Option<MessageToken<'a>> {
+        let MessageToken::Impure(slice) = tok else {
+            return None;
+        };
+        if self.uuid.is_match(slice) {
+            Some(MessageToken::Uuid(slice))
+        } else if self.ip_addr.is_match(slice) {
+            Some(MessageToken::IpAddr(slice))
+        } else if self.path.is_match(slice) {
+            Some(MessageToken::Path(slice))
+        } else if self.float.is_match(slice) {
+            Some(MessageToken::Float(slice))
+        } else if self.hex.is_match(slice) {
+            Some(MessageToken::Hex(slice))
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
 pub enum MessageToken<'a> {
     Alphabetic(&'a str),
@@ -147,6 +221,22 @@ pub enum MessageToken<'a> {
     // Special(&'a str),
     SpecialWhite(&'a str),
     SpecialBlack(&'a str),
+    /// A decimal floating-point literal, e.g. `12.3114`. Only produced when
+    /// [`Tokenizer::with_semantic_detection`] is enabled.
+    Float(&'a str),
+    /// A hexadecimal literal, e.g. `0x1A2B` or `1a2b3c4d`. Only produced when
+    /// [`Tokenizer::with_semantic_detection`] is enabled. A purely alphabetic
+    /// hex string such as `deadbeef` stays `Alphabetic`.
+    Hex(&'a str),
+    /// A dotted-quad IPv4 address. Only produced when
+    /// [`Tokenizer::with_semantic_detection`] is enabled.
+    IpAddr(&'a str),
+    /// A UUID. Only produced when [`Tokenizer::with_semantic_detection`] is enabled.
+    Uuid(&'a str),
+    /// A slash-delimited filesystem path, e.g. `/var/log/syslog` or
+    /// `etc/hosts`. Only produced when [`Tokenizer::with_semantic_detection`]
+    /// is enabled.
+    Path(&'a str),
 }
 
 impl<'a> MessageToken<'a> {
@@ -178,11 +268,29 @@ impl<'a> MessageToken<'a> {
             MessageToken::Impure(slice) => slice,
             MessageToken::SpecialWhite(slice) => slice,
             MessageToken::SpecialBlack(slice) => slice,
+            MessageToken::Float(slice) => slice,
+            MessageToken::Hex(slice) => slice,
+            MessageToken::IpAddr(slice) => slice,
+            MessageToken::Uuid(slice) => slice,
+            MessageToken::Path(slice) => slice,
             // Token::Special(slice) => slice,
         }
     }
 }
 
+/// Default token filter for callers (e.g. [`crate::interdependency::Interdependency`])
+/// that just want a sensible out-of-the-box key-token candidate set instead of
+/// hand-rolling a `match` over every variant: alphabetic and whitelisted-special
+/// tokens are kept, while numeric, symbolic, whitespace, and the semantic
+/// classes (`Float`, `Hex`, `IpAddr`, `Uuid`, `Path`) are treated as parameters
+/// and excluded.
+pub fn default_token_filter(tok: &MessageToken) -> bool {
+    matches!(
+        tok,
+        MessageToken::Alphabetic(_) | MessageToken::SpecialWhite(_)
+    )
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum PreToken<'a> {
     // Special(&'a str),
@@ -217,6 +325,78 @@ mod tests {
         assert_eq!(expected, computed);
     }
 
+    #[test]
+    fn semantic_patterns_reclassify() {
+        let patterns = SemanticPatterns::new();
+        let symbols = HashSet::default();
+
+        let classify = |slice: &'static str| {
+            patterns.reclassify(MessageToken::with(slice, &symbols))
+        };
+
+        assert_eq!(classify("12.3114"), Some(MessageToken::Float("12.3114")));
+        // A purely-alphabetic hex literal like "deadbeef" is classified
+        // `Alphabetic` before `reclassify` ever sees it (only `Impure`
+        // slices are eligible), so only digit-bearing hex strings reach
+        // the `Hex` class.
+        assert_eq!(classify("1a2b3c4d"), Some(MessageToken::Hex("1a2b3c4d")));
+        assert_eq!(classify("0x1A2B"), Some(MessageToken::Hex("0x1A2B")));
+        assert_eq!(classify("deadbeef"), None);
+        assert_eq!(
+            classify("192.168.1.1"),
+            Some(MessageToken::IpAddr("192.168.1.1"))
+        );
+        assert_eq!(
+            classify("550e8400-e29b-41d4-a716-446655440000"),
+            Some(MessageToken::Uuid("550e8400-e29b-41d4-a716-446655440000"))
+        );
+        assert_eq!(
+            classify("/var/log/syslog"),
+            Some(MessageToken::Path("/var/log/syslog"))
+        );
+        assert_eq!(classify("not_semantic"), None);
+    }
+
+    #[test]
+    fn tokenizer_tokenize_with_semantic_detection() {
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), ",".chars().collect())
+            .with_semantic_detection(true);
+        let computed = tokenizer.tokenize("req 192.168.1.1 took 12.3114 ms, path /var/log/syslog");
+        let expected = vec![
+            MessageToken::Alphabetic("req"),
+            MessageToken::Whitespace(" "),
+            MessageToken::IpAddr("192.168.1.1"),
+            MessageToken::Whitespace(" "),
+            MessageToken::Alphabetic("took"),
+            MessageToken::Whitespace(" "),
+            MessageToken::Float("12.3114"),
+            MessageToken::Whitespace(" "),
+            MessageToken::Alphabetic("ms"),
+            MessageToken::Symbolic(","),
+            MessageToken::Whitespace(" "),
+            MessageToken::Alphabetic("path"),
+            MessageToken::Whitespace(" "),
+            MessageToken::Path("/var/log/syslog"),
+        ];
+        assert_eq!(expected, computed);
+    }
+
+    #[test]
+    fn tokenizer_tokenize_without_semantic_detection_stays_impure() {
+        let tokenizer = Tokenizer::new(Vec::new(), Vec::new(), Default::default());
+        let computed = tokenizer.tokenize("took 12.3114 ms");
+        assert_eq!(
+            computed,
+            vec![
+                MessageToken::Alphabetic("took"),
+                MessageToken::Whitespace(" "),
+                MessageToken::Impure("12.3114"),
+                MessageToken::Whitespace(" "),
+                MessageToken::Alphabetic("ms"),
+            ]
+        );
+    }
+
     #[test]
     fn tokenizer_tokenize() {
         let tokenizer = Tokenizer::new(